@@ -0,0 +1,59 @@
+#[cfg(feature = "serde")]
+use alloc::string::String;
+use core::fmt;
+
+/// Errors returned by fallible garble operations
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Rate is not a probability between 0.0 and 1.0
+    InvalidRate(f64),
+    /// Weight is negative or not a finite number, or makes the total weight
+    /// infinite
+    InvalidWeight(f64),
+    /// Garbled protobuf message could not be decoded
+    #[cfg(feature = "prost")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
+    Prost(prost::DecodeError),
+    /// Value could not be serialized or deserialized with serde
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    Serde(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRate(rate) => {
+                write!(
+                    f,
+                    "invalid rate {rate}, expected a value between 0.0 and 1.0"
+                )
+            }
+            Self::InvalidWeight(weight) => {
+                write!(
+                    f,
+                    "invalid weight {weight}, expected a finite positive value"
+                )
+            }
+            #[cfg(feature = "prost")]
+            Self::Prost(err) => write!(f, "protobuf decode error: {err}"),
+            #[cfg(feature = "serde")]
+            Self::Serde(msg) => write!(f, "serde error: {msg}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for Error {}
+
+/// Check that a rate is a valid probability
+#[cfg(feature = "simple")]
+pub(crate) fn check_rate(rate: f64) -> Result<f64, Error> {
+    if (0.0..=1.0).contains(&rate) {
+        Ok(rate)
+    } else {
+        Err(Error::InvalidRate(rate))
+    }
+}
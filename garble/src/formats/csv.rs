@@ -0,0 +1,367 @@
+//! Field and record faults for CSV and other delimited text
+//!
+//! ```rust
+//! use garble::{formats::csv, SimpleGarbler};
+//!
+//! let mut garbler = SimpleGarbler::builder()
+//!     .rate_for_str(0.05)
+//!     .structural_rate(0.1)
+//!     .build()
+//!     .unwrap();
+//! dbg!(csv::garble_text("id,name\n1,\"Doe, Jane\"\n2,Smith\n", ',', &mut garbler));
+//! // Tab-separated values
+//! dbg!(csv::garble_text("id\tname\n1\tDoe\n", '\t', &mut garbler));
+//! ```
+
+use crate::Garbler;
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
+/// Number of faults that can be applied to a field
+const FIELD_FAULTS: usize = 3;
+
+/// Field of a record
+#[derive(Debug, PartialEq)]
+struct Field<'a> {
+    /// Content of the field, without quotes or escapes
+    content: Cow<'a, str>,
+    quoted: bool,
+}
+
+/// Record, made of fields and followed by a line ending
+#[derive(Debug, PartialEq)]
+struct Record<'a> {
+    fields: Vec<Field<'a>>,
+    /// `"\n"`, `"\r\n"`, or empty for a final line without line ending
+    line_ending: &'a str,
+}
+
+/// Parse the first record of the text, returning the remaining text
+///
+/// Malformed text, such as characters after a closing quote or unterminated
+/// quotes, is parsed leniently.
+fn parse_record(mut text: &str, delimiter: char) -> (Record<'_>, &str) {
+    let mut fields = Vec::new();
+    loop {
+        let (field, rest) = match text.strip_prefix('"') {
+            Some(quoted) => parse_quoted(quoted, delimiter),
+            None => {
+                let end = field_end(text, delimiter);
+                let field = Field {
+                    content: Cow::Borrowed(&text[..end]),
+                    quoted: false,
+                };
+                (field, &text[end..])
+            }
+        };
+        fields.push(field);
+        text = rest;
+
+        if let Some(rest) = text.strip_prefix(delimiter) {
+            text = rest;
+            continue;
+        }
+        let line_ending = ["\r\n", "\n"]
+            .into_iter()
+            .find(|ending| text.starts_with(ending))
+            .unwrap_or("");
+        let (line_ending, rest) = text.split_at(line_ending.len());
+        return (
+            Record {
+                fields,
+                line_ending,
+            },
+            rest,
+        );
+    }
+}
+
+/// Find the end of an unquoted field
+///
+/// Carriage returns that are not part of a line ending are kept in the
+/// content of the field.
+fn field_end(text: &str, delimiter: char) -> usize {
+    text.char_indices()
+        .find(|&(index, c)| {
+            c == delimiter || c == '\n' || (c == '\r' && text[index + 1..].starts_with('\n'))
+        })
+        .map_or(text.len(), |(index, _)| index)
+}
+
+/// Parse a quoted field, after the opening quote
+fn parse_quoted(text: &str, delimiter: char) -> (Field<'_>, &str) {
+    let mut content = String::new();
+    let mut rest = text;
+    loop {
+        match rest.find('"') {
+            Some(index) if rest[index + 1..].starts_with('"') => {
+                // Escaped quote
+                content.push_str(&rest[..=index]);
+                rest = &rest[index + 2..];
+            }
+            Some(index) => {
+                content.push_str(&rest[..index]);
+                rest = &rest[index + 1..];
+                break;
+            }
+            None => {
+                // Unterminated quote
+                content.push_str(rest);
+                rest = "";
+                break;
+            }
+        }
+    }
+
+    // Characters between the closing quote and the end of the field
+    let end = field_end(rest, delimiter);
+    content.push_str(&rest[..end]);
+    let field = Field {
+        content: Cow::Owned(content),
+        quoted: true,
+    };
+    (field, &rest[end..])
+}
+
+/// Write a field, quoting it if needed
+///
+/// If `closed` is false, the closing quote is omitted.
+fn write_field(content: &str, quoted: bool, closed: bool, delimiter: char, output: &mut String) {
+    let needs_quotes = content.contains([delimiter, '"', '\n', '\r']);
+    if !quoted && !needs_quotes && closed {
+        output.push_str(content);
+        return;
+    }
+
+    output.push('"');
+    output.push_str(&content.replace('"', "\"\""));
+    if closed {
+        output.push('"');
+    }
+}
+
+/// Truncate the line starting at `start`, cutting it in the middle and
+/// dropping its line ending
+///
+/// At least one byte of the line is kept, so that short lines are truncated
+/// rather than dropped.
+fn truncate_line(output: &mut String, start: usize) {
+    let len = output[start..].trim_end_matches(['\r', '\n']).len();
+    let mut end = start + (len / 2).max(1).min(len);
+    while !output.is_char_boundary(end) {
+        end += 1;
+    }
+    output.truncate(end);
+}
+
+/// Garble delimited text field by field
+///
+/// The content of each field is garbled with [`Garbler::garble_str`], and
+/// re-quoted as needed so that the text stays valid. The following faults are
+/// also applied:
+///
+/// * Missing columns
+/// * Extra delimiters
+/// * Broken quoting, with a field missing its closing quote
+/// * Mixed line endings, swapping `\n` and `\r\n`
+/// * Truncated final line
+///
+/// Each field and each line ending is checked for a fault with
+/// [`Garbler::structural_fault`]. The final line is checked once for
+/// truncation. Fields are separated by `delimiter`, such as `','` for CSV or
+/// `'\t'` for TSV, and can be quoted with double quotes.
+pub fn garble_text<T, G>(text: T, delimiter: char, garbler: &mut G) -> String
+where
+    T: AsRef<str>,
+    G: Garbler,
+{
+    let mut rest = text.as_ref();
+    let mut output = String::with_capacity(rest.len());
+    let mut last_record = None;
+
+    while !rest.is_empty() {
+        let (record, tail) = parse_record(rest, delimiter);
+        last_record = Some(output.len());
+
+        let mut first = true;
+        for field in record.fields {
+            let content = garbler.garble_str(&field.content);
+            let fault = garbler.structural_fault(FIELD_FAULTS);
+            // Missing column
+            if fault == Some(0) {
+                continue;
+            }
+
+            if !first {
+                output.push(delimiter);
+            }
+            first = false;
+            // Broken quoting
+            let closed = fault != Some(2);
+            write_field(&content, field.quoted, closed, delimiter, &mut output);
+            // Extra delimiter
+            if fault == Some(1) {
+                output.push(delimiter);
+            }
+        }
+
+        // Mixed line endings
+        let swap = !record.line_ending.is_empty() && garbler.structural_fault(1).is_some();
+        let line_ending = match (record.line_ending, swap) {
+            ("\r\n", true) => "\n",
+            (_, true) => "\r\n",
+            (line_ending, false) => line_ending,
+        };
+        output.push_str(line_ending);
+        rest = tail;
+    }
+
+    // Truncated final line
+    if let Some(start) = last_record {
+        if garbler.structural_fault(1).is_some() {
+            truncate_line(&mut output, start);
+        }
+    }
+    output
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::CountingGarbler;
+
+    fn field(content: &str, quoted: bool) -> Field<'_> {
+        Field {
+            content: Cow::Borrowed(content),
+            quoted,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        // GIVEN CSV text
+        let mut text = "a,\"b,\"\"c\"\"\",\n\"d\ne\"\r\nf\rg";
+        // WHEN we parse its records
+        let mut records = Vec::new();
+        while !text.is_empty() {
+            let (record, rest) = parse_record(text, ',');
+            records.push(record);
+            text = rest;
+        }
+        // THEN each record should be parsed
+        assert_eq!(
+            records,
+            vec![
+                Record {
+                    fields: vec![field("a", false), field("b,\"c\"", true), field("", false)],
+                    line_ending: "\n",
+                },
+                Record {
+                    fields: vec![field("d\ne", true)],
+                    line_ending: "\r\n",
+                },
+                Record {
+                    fields: vec![field("f\rg", false)],
+                    line_ending: "",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_unterminated() {
+        // GIVEN a field with an unterminated quote
+        let text = "\"a,b\nc";
+        // WHEN we parse the record
+        let (record, rest) = parse_record(text, ',');
+        // THEN it should span the whole text
+        assert_eq!(record.fields, vec![field("a,b\nc", true)]);
+        assert_eq!(rest, "");
+    }
+
+    #[test]
+    fn test_write_field() {
+        // GIVEN fields
+        let cases = [
+            ("a", false, true, "a"),
+            ("a", true, true, "\"a\""),
+            ("a\tb", false, true, "\"a\tb\""),
+            ("a\"b", false, true, "\"a\"\"b\""),
+            ("a", false, false, "\"a"),
+        ];
+        for (content, quoted, closed, expected) in cases {
+            // WHEN we write them
+            let mut output = String::new();
+            write_field(content, quoted, closed, '\t', &mut output);
+            // THEN they should be quoted as needed
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_truncate_line() {
+        // GIVEN final lines
+        let cases = [
+            ("a,b\ncd,ef\n", "a,b\ncd"),
+            ("a,b\nc\r\n", "a,b\nc"),
+            ("a,b\né\n", "a,b\né"),
+            ("a,b\n\n", "a,b\n"),
+        ];
+        for (text, expected) in cases {
+            // WHEN we truncate them
+            let mut output = String::from(text);
+            truncate_line(&mut output, 4);
+            // THEN they should be cut in the middle, keeping at least one byte
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_passthrough() {
+        // GIVEN CSV text
+        let text = "id,name\r\n1,\"Doe, Jane\"\r\n2,Smith";
+        // WHEN we garble it with a garbler that does not change anything
+        let mut garbler = CountingGarbler::new();
+        let garbled = garble_text(text, ',', &mut garbler);
+        // THEN the text should be unchanged
+        assert_eq!(garbled, text);
+        // AND every field should be garbled
+        assert_eq!(garbler.count(), 6);
+    }
+
+    #[cfg(feature = "simple")]
+    #[test]
+    fn test_faults() {
+        use crate::SimpleGarbler;
+
+        // GIVEN a garbler that always applies structural faults, but never
+        // garbles strings
+        let mut garbler = SimpleGarbler::builder()
+            .disable_str()
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble CSV text
+        let text = "a,b\nc,d\n";
+        let garbled = garble_text(text, ',', &mut garbler);
+        // THEN the line endings should be swapped
+        assert!(garbled.contains("\r\n"));
+        // AND the final line should be truncated, with its line ending
+        assert_eq!(
+            garbled.matches('\n').count(),
+            text.matches('\n').count() - 1
+        );
+    }
+
+    #[cfg(feature = "simple")]
+    #[test]
+    fn test_zero_rate() {
+        use crate::SimpleGarbler;
+
+        // GIVEN a garbler that never garbles anything
+        let mut garbler = SimpleGarbler::new(0.0).unwrap();
+        // WHEN we garble TSV text
+        let text = "a\tb\nc\t\"d\"\n";
+        // THEN it should be unchanged
+        assert_eq!(garble_text(text, '\t', &mut garbler), text);
+    }
+}
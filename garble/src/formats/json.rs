@@ -0,0 +1,308 @@
+//! Token-level faults for JSON text
+//!
+//! ```rust
+//! use garble::{formats::json, SimpleGarbler};
+//!
+//! let mut garbler = SimpleGarbler::builder()
+//!     .rate(0.0)
+//!     .structural_rate(0.1)
+//!     .build()
+//!     .unwrap();
+//! dbg!(json::garble_text(r#"{"name":"garble","retries":[1,2,3]}"#, &mut garbler));
+//! ```
+
+use crate::Garbler;
+use alloc::string::String;
+
+/// Number of digits of overflowing numbers, which is more than any integer or
+/// floating-point type can hold
+const OVERFLOW_DIGITS: usize = 400;
+
+/// Garbage appended after the end of a document
+const TRAILING_GARBAGE: [&str; 4] = ["}", ",", " null", "\0"];
+
+/// Byte order mark, which JSON text must not start with
+const BOM: char = '\u{FEFF}';
+
+/// Kind of a JSON token
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    /// Opening or closing brace or bracket
+    Bracket,
+    /// Comma or colon
+    Separator,
+    /// String, including its quotes
+    String { closed: bool },
+    /// Number
+    Number,
+    /// `true`, `false` or `null`
+    Literal,
+    /// Whitespace or any other character
+    Other,
+}
+
+impl Token {
+    /// Number of faults that can be applied to the token
+    fn faults(self) -> usize {
+        match self {
+            Self::Bracket | Self::Separator => 2,
+            Self::String { .. } => 4,
+            Self::Number => 2,
+            Self::Literal => 1,
+            Self::Other => 0,
+        }
+    }
+}
+
+/// Garble JSON text at the token level
+///
+/// Unlike garbling a [`serde_json::Value`], this produces malformed JSON, by
+/// applying the following faults:
+///
+/// * Byte order mark at the start of the text
+/// * Dropped or duplicated braces, brackets, commas and colons
+/// * Unterminated strings, invalid escapes, truncated Unicode escapes and lone
+///   surrogates
+/// * Numbers overflowing any integer or floating-point type
+/// * Truncated `true`, `false` and `null` literals
+/// * Trailing garbage after the end of the text
+///
+/// Each token is checked for a fault with
+/// [`Garbler::structural_fault`], and other text is kept as-is. The input does
+/// not need to be valid JSON.
+pub fn garble_text<T, G>(text: T, garbler: &mut G) -> String
+where
+    T: AsRef<str>,
+    G: Garbler,
+{
+    let mut rest = text.as_ref();
+    let mut output = String::with_capacity(rest.len());
+
+    if garbler.structural_fault(1).is_some() {
+        output.push(BOM);
+    }
+
+    while !rest.is_empty() {
+        let (token, len) = next_token(rest);
+        let (value, tail) = rest.split_at(len);
+        match garbler.structural_fault(token.faults()) {
+            Some(fault) => apply_fault(token, value, fault, &mut output),
+            None => output.push_str(value),
+        }
+        rest = tail;
+    }
+
+    if let Some(fault) = garbler.structural_fault(TRAILING_GARBAGE.len()) {
+        output.push_str(TRAILING_GARBAGE[fault]);
+    }
+    output
+}
+
+/// Find the kind and length in bytes of the first token of the text
+fn next_token(text: &str) -> (Token, usize) {
+    let bytes = text.as_bytes();
+    let run = |f: fn(&u8) -> bool| bytes.iter().position(|b| !f(b)).unwrap_or(bytes.len());
+
+    match bytes[0] {
+        b'{' | b'}' | b'[' | b']' => (Token::Bracket, 1),
+        b',' | b':' => (Token::Separator, 1),
+        b'"' => {
+            // Quotes and backslashes are ASCII, so they can never be part of
+            // a multi-byte character.
+            let mut index = 1;
+            while index < bytes.len() {
+                match bytes[index] {
+                    b'\\' => index += 2,
+                    b'"' => return (Token::String { closed: true }, index + 1),
+                    _ => index += 1,
+                }
+            }
+            (Token::String { closed: false }, bytes.len())
+        }
+        b'-' | b'0'..=b'9' => (
+            Token::Number,
+            run(|b| matches!(b, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')),
+        ),
+        b if b.is_ascii_alphabetic() => (Token::Literal, run(u8::is_ascii_alphabetic)),
+        b if b.is_ascii_whitespace() => (Token::Other, run(u8::is_ascii_whitespace)),
+        _ => (Token::Other, text.chars().next().unwrap().len_utf8()),
+    }
+}
+
+/// Write a token with the given fault applied
+fn apply_fault(token: Token, value: &str, fault: usize, output: &mut String) {
+    match (token, fault) {
+        // Dropped token
+        (Token::Bracket | Token::Separator, 0) => {}
+        // Duplicated token
+        (Token::Bracket | Token::Separator, _) => {
+            output.push_str(value);
+            output.push_str(value);
+        }
+        (Token::String { closed }, fault) => {
+            let content = if closed {
+                &value[..value.len() - 1]
+            } else {
+                value
+            };
+            output.push_str(content);
+            match fault {
+                // Unterminated string
+                0 => return,
+                // Invalid escape
+                1 => output.push_str("\\x"),
+                // Truncated Unicode escape
+                2 => output.push_str("\\u12"),
+                // Lone surrogate
+                _ => output.push_str("\\ud800"),
+            }
+            if closed {
+                output.push('"');
+            }
+        }
+        (Token::Number, fault) => {
+            if value.starts_with('-') {
+                output.push('-');
+            }
+            match fault {
+                // Integer overflow
+                0 => output.extend(core::iter::repeat('9').take(OVERFLOW_DIGITS)),
+                // Exponent overflow
+                _ => output.push_str("1e999"),
+            }
+        }
+        // Truncated literal
+        (Token::Literal, _) => {
+            let mut chars = value.chars();
+            chars.next_back();
+            output.push_str(chars.as_str());
+        }
+        (Token::Other, _) => output.push_str(value),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::CountingGarbler;
+
+    #[test]
+    fn test_tokens() {
+        // GIVEN JSON text
+        let mut text = r#"{"a\"b": [-1.5e3, true, null]} "#;
+        // WHEN we split it into tokens
+        let mut tokens = Vec::new();
+        while !text.is_empty() {
+            let (token, len) = next_token(text);
+            tokens.push((token, &text[..len]));
+            text = &text[len..];
+        }
+        // THEN each token should have the right kind
+        assert_eq!(
+            tokens,
+            vec![
+                (Token::Bracket, "{"),
+                (Token::String { closed: true }, r#""a\"b""#),
+                (Token::Separator, ":"),
+                (Token::Other, " "),
+                (Token::Bracket, "["),
+                (Token::Number, "-1.5e3"),
+                (Token::Separator, ","),
+                (Token::Other, " "),
+                (Token::Literal, "true"),
+                (Token::Separator, ","),
+                (Token::Other, " "),
+                (Token::Literal, "null"),
+                (Token::Bracket, "]"),
+                (Token::Bracket, "}"),
+                (Token::Other, " "),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_token() {
+        // GIVEN an unterminated string ending with an escaped quote
+        let text = r#""abc\""#;
+        // WHEN we read the first token
+        // THEN it should span the whole text
+        assert_eq!(
+            next_token(text),
+            (Token::String { closed: false }, text.len())
+        );
+    }
+
+    #[test]
+    fn test_faults() {
+        // GIVEN tokens and faults
+        let cases = [
+            (Token::Bracket, "{", 0, ""),
+            (Token::Separator, ",", 1, ",,"),
+            (Token::String { closed: true }, r#""ab""#, 0, r#""ab"#),
+            (Token::String { closed: true }, r#""ab""#, 1, r#""ab\x""#),
+            (Token::String { closed: true }, r#""ab""#, 2, r#""ab\u12""#),
+            (Token::String { closed: false }, r#""ab"#, 3, r#""ab\ud800"#),
+            (Token::Number, "-12", 1, "-1e999"),
+            (Token::Literal, "true", 0, "tru"),
+        ];
+        for (token, value, fault, expected) in cases {
+            // WHEN we apply the fault
+            let mut output = String::new();
+            apply_fault(token, value, fault, &mut output);
+            // THEN the token should be garbled
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_number_overflow() {
+        // GIVEN a number
+        // WHEN we apply an integer overflow
+        let mut output = String::new();
+        apply_fault(Token::Number, "42", 0, &mut output);
+        // THEN it should not fit in any number type
+        assert_eq!(output, "9".repeat(OVERFLOW_DIGITS));
+        assert!(serde_json::from_str::<serde_json::Value>(&output).is_err());
+    }
+
+    #[test]
+    fn test_passthrough() {
+        // GIVEN JSON text
+        let text = r#"{"name": "garble", "retries": [1, 2, 3]}"#;
+        // WHEN we garble it with a garbler that never applies structural faults
+        let garbled = garble_text(text, &mut CountingGarbler::new());
+        // THEN the text should be unchanged
+        assert_eq!(garbled, text);
+    }
+
+    #[cfg(feature = "simple")]
+    #[test]
+    fn test_malformed() {
+        use crate::SimpleGarbler;
+
+        // GIVEN a garbler that always applies structural faults
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble JSON text
+        let garbled = garble_text(r#"{"a": [1, true]}"#, &mut garbler);
+        // THEN it should start with a byte order mark and fail to parse
+        assert!(garbled.starts_with(BOM));
+        assert!(serde_json::from_str::<serde_json::Value>(&garbled).is_err());
+    }
+
+    #[cfg(feature = "simple")]
+    #[test]
+    fn test_zero_rate() {
+        use crate::SimpleGarbler;
+
+        // GIVEN a garbler that never applies structural faults
+        let mut garbler = SimpleGarbler::new(0.0).unwrap();
+        // WHEN we garble JSON text
+        let text = r#"{"a": [1, true]}"#;
+        // THEN it should be unchanged
+        assert_eq!(garble_text(text, &mut garbler), text);
+    }
+}
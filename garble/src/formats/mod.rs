@@ -0,0 +1,11 @@
+//! Garblers for serialized data formats
+//!
+//! Garbling values produces well-formed data with wrong values. The garblers
+//! in this module instead corrupt the encoding of the data itself, to test the
+//! error paths of parsers. Faults are picked with
+//! [`Garbler::structural_fault`](crate::Garbler::structural_fault), so they
+//! follow the structural rate of a [`SimpleGarbler`](crate::SimpleGarbler).
+
+pub mod csv;
+pub mod json;
+pub mod protobuf;
@@ -0,0 +1,528 @@
+//! Faults for the Protocol Buffers wire format
+//!
+//! Encoded messages do not carry their schema, so fields are garbled based on
+//! their wire type. Length-delimited fields are garbled as nested messages if
+//! they can be parsed as one, as packed varints if they can be parsed as a
+//! sequence of varints, as text if they are valid UTF-8, and as raw bytes
+//! otherwise. Short strings can look like nested messages, and packed values
+//! can look like text, so this is a best effort.
+//!
+//! ```rust
+//! use garble::{formats::protobuf, SimpleGarbler};
+//!
+//! let mut garbler = SimpleGarbler::new(0.1).unwrap();
+//! // Field 1 with the varint 150, and field 2 with the string "hi"
+//! let bytes = [0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i'];
+//! dbg!(protobuf::garble_fields(bytes, &mut garbler));
+//! dbg!(protobuf::garble_wire(bytes, &mut garbler));
+//! ```
+
+use crate::{Garble, Garbler};
+use alloc::vec::Vec;
+use core::str;
+
+/// Largest valid field number
+const MAX_FIELD_NUMBER: u64 = (1 << 29) - 1;
+
+/// Maximum length of an encoded varint
+const MAX_VARINT_LEN: usize = 10;
+
+/// Number of wire-level faults that can be applied to a field
+const WIRE_FAULTS: usize = 6;
+
+/// Wire type of a field
+///
+/// Deprecated groups are not supported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WireType {
+    Varint,
+    I64,
+    Len,
+    I32,
+}
+
+impl WireType {
+    fn from_tag(tag: u64) -> Option<Self> {
+        match tag & 0b111 {
+            0 => Some(Self::Varint),
+            1 => Some(Self::I64),
+            2 => Some(Self::Len),
+            5 => Some(Self::I32),
+            _ => None,
+        }
+    }
+
+    fn bits(self) -> u64 {
+        match self {
+            Self::Varint => 0,
+            Self::I64 => 1,
+            Self::Len => 2,
+            Self::I32 => 5,
+        }
+    }
+
+    /// Another wire type, so that the value is read incorrectly
+    fn next(self) -> Self {
+        match self {
+            Self::Varint => Self::I64,
+            Self::I64 => Self::Len,
+            Self::Len => Self::I32,
+            Self::I32 => Self::Varint,
+        }
+    }
+}
+
+/// Field of an encoded message
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Field<'a> {
+    number: u64,
+    wire_type: WireType,
+    /// Encoded value, without the length prefix of length-delimited fields
+    value: &'a [u8],
+}
+
+impl<'a> Field<'a> {
+    /// Parse the first field of the bytes, returning the remaining bytes
+    fn parse(bytes: &'a [u8]) -> Option<(Self, &'a [u8])> {
+        let (tag, mut bytes) = read_varint(bytes)?;
+        let number = tag >> 3;
+        if number == 0 || number > MAX_FIELD_NUMBER {
+            return None;
+        }
+
+        let wire_type = WireType::from_tag(tag)?;
+        let len = match wire_type {
+            WireType::Varint => bytes.len() - read_varint(bytes)?.1.len(),
+            WireType::I64 => 8,
+            WireType::Len => {
+                let (len, rest) = read_varint(bytes)?;
+                bytes = rest;
+                usize::try_from(len).ok()?
+            }
+            WireType::I32 => 4,
+        };
+        if len > bytes.len() {
+            return None;
+        }
+
+        let (value, rest) = bytes.split_at(len);
+        Some((
+            Self {
+                number,
+                wire_type,
+                value,
+            },
+            rest,
+        ))
+    }
+
+    /// Write the field with another value
+    fn with_value(self, value: &[u8], output: &mut Vec<u8>) {
+        Field { value, ..self }.write(output)
+    }
+
+    fn write(self, output: &mut Vec<u8>) {
+        write_varint(self.number << 3 | self.wire_type.bits(), output);
+        self.write_value(output);
+    }
+
+    /// Write the value, including the length prefix of length-delimited
+    /// fields
+    fn write_value(self, output: &mut Vec<u8>) {
+        if self.wire_type == WireType::Len {
+            write_varint(self.value.len() as u64, output);
+        }
+        output.extend_from_slice(self.value);
+    }
+}
+
+/// Content of a length-delimited field
+#[derive(Debug, PartialEq)]
+enum Payload<'a> {
+    Message,
+    Packed,
+    Text(&'a str),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> Payload<'a> {
+    fn classify(value: &'a [u8]) -> Self {
+        let text = str::from_utf8(value).ok();
+        // Nested messages almost always start with a control character, as
+        // the tags of the first fields are below 0x20, while text rarely does.
+        // Empty values are kept as empty messages, as proto3 does not encode
+        // empty strings unless they are repeated or optional.
+        let is_text = text.is_some_and(|text| !text.contains(char::is_control));
+        if value.is_empty() || (!is_text && is_message(value)) {
+            return Self::Message;
+        }
+        // Packed repeated scalars are checked after messages, as messages
+        // with only varint fields keep their varints valid when garbled.
+        if !is_text && is_packed(value) {
+            return Self::Packed;
+        }
+
+        match text {
+            Some(text) => Self::Text(text),
+            None => Self::Bytes(value),
+        }
+    }
+}
+
+/// Whether the bytes can be parsed as a sequence of fields
+fn is_message(mut bytes: &[u8]) -> bool {
+    while !bytes.is_empty() {
+        match Field::parse(bytes) {
+            Some((_, rest)) => bytes = rest,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Whether the bytes can be parsed as a sequence of varints
+///
+/// Encoders never write trailing zero bytes in varints, so varints ending
+/// with one are most likely raw bytes.
+fn is_packed(mut bytes: &[u8]) -> bool {
+    while !bytes.is_empty() {
+        let rest = match read_varint(bytes) {
+            Some((_, rest)) => rest,
+            None => return false,
+        };
+        let len = bytes.len() - rest.len();
+        if len > 1 && bytes[len - 1] == 0 {
+            return false;
+        }
+        bytes = rest;
+    }
+    true
+}
+
+fn read_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value = 0;
+    for (index, byte) in bytes.iter().take(MAX_VARINT_LEN).enumerate() {
+        value |= u64::from(byte & 0x7F) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[index + 1..]));
+        }
+    }
+    None
+}
+
+fn write_varint(mut value: u64, output: &mut Vec<u8>) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Garble the values of the fields of an encoded message
+///
+/// This keeps the encoding valid, and garbles each value with the
+/// [`Garbler`]: varints, including packed varints, and 64-bit values as `u64`,
+/// 32-bit values as `u32`, text with [`Garbler::garble_str`] and bytes as
+/// `u8`. Nested messages are garbled recursively. Bytes that cannot be parsed
+/// are kept as-is.
+pub fn garble_fields<T, G>(bytes: T, garbler: &mut G) -> Vec<u8>
+where
+    T: AsRef<[u8]>,
+    G: Garbler,
+{
+    let bytes = bytes.as_ref();
+    let mut output = Vec::with_capacity(bytes.len());
+    write_garbled_fields(bytes, garbler, &mut output);
+    output
+}
+
+fn write_garbled_fields<G>(mut bytes: &[u8], garbler: &mut G, output: &mut Vec<u8>)
+where
+    G: Garbler,
+{
+    while let Some((field, rest)) = Field::parse(bytes) {
+        let mut value = Vec::with_capacity(field.value.len());
+        match field.wire_type {
+            WireType::Varint => {
+                let (varint, _) = read_varint(field.value).unwrap();
+                write_varint(garbler.garble_u64(varint), &mut value);
+            }
+            WireType::I64 => {
+                let fixed = u64::from_le_bytes(field.value.try_into().unwrap());
+                value.extend_from_slice(&garbler.garble_u64(fixed).to_le_bytes());
+            }
+            WireType::I32 => {
+                let fixed = u32::from_le_bytes(field.value.try_into().unwrap());
+                value.extend_from_slice(&garbler.garble_u32(fixed).to_le_bytes());
+            }
+            WireType::Len => match Payload::classify(field.value) {
+                Payload::Message => write_garbled_fields(field.value, garbler, &mut value),
+                Payload::Packed => {
+                    let mut packed = field.value;
+                    while let Some((varint, rest)) = read_varint(packed) {
+                        write_varint(garbler.garble_u64(varint), &mut value);
+                        packed = rest;
+                    }
+                }
+                Payload::Text(text) => value = garbler.garble_str(text).into_bytes(),
+                Payload::Bytes(bytes) => value = bytes.garble(garbler),
+            },
+        }
+        field.with_value(&value, output);
+        bytes = rest;
+    }
+    output.extend_from_slice(bytes);
+}
+
+/// Garble an encoded message at the wire level
+///
+/// This produces malformed or unexpected encodings, by applying the following
+/// faults to fields, including the fields of nested messages:
+///
+/// * Flipped field numbers
+/// * Changed wire types, so that the value is read incorrectly
+/// * Unterminated varints
+/// * Dropped or duplicated fields
+/// * Unknown fields
+///
+/// Each field is checked for a fault with [`Garbler::structural_fault`]. Bytes
+/// that cannot be parsed are kept as-is.
+pub fn garble_wire<T, G>(bytes: T, garbler: &mut G) -> Vec<u8>
+where
+    T: AsRef<[u8]>,
+    G: Garbler,
+{
+    let bytes = bytes.as_ref();
+    let mut output = Vec::with_capacity(bytes.len());
+    write_garbled_wire(bytes, garbler, &mut output);
+    output
+}
+
+fn write_garbled_wire<G>(mut bytes: &[u8], garbler: &mut G, output: &mut Vec<u8>)
+where
+    G: Garbler,
+{
+    while let Some((field, rest)) = Field::parse(bytes) {
+        match garbler.structural_fault(WIRE_FAULTS) {
+            Some(fault) => apply_fault(field, fault, output),
+            None if field.wire_type == WireType::Len
+                && Payload::classify(field.value) == Payload::Message =>
+            {
+                let mut value = Vec::with_capacity(field.value.len());
+                write_garbled_wire(field.value, garbler, &mut value);
+                field.with_value(&value, output);
+            }
+            None => field.write(output),
+        }
+        bytes = rest;
+    }
+    output.extend_from_slice(bytes);
+}
+
+/// Write a field with the given fault applied
+fn apply_fault(field: Field<'_>, fault: usize, output: &mut Vec<u8>) {
+    match fault {
+        // Flipped field number, which can also produce the invalid field 0
+        0 => Field {
+            number: field.number ^ 1,
+            ..field
+        }
+        .write(output),
+        // Changed wire type
+        1 => {
+            write_varint(field.number << 3 | field.wire_type.next().bits(), output);
+            field.write_value(output);
+        }
+        // Unterminated varint, which runs into the next bytes
+        2 => {
+            field.write(output);
+            // The value of varints, the length prefix of length-delimited
+            // fields or the tag of fixed-size fields
+            let at = match field.wire_type {
+                WireType::Varint => output.len() - 1,
+                _ => output.len() - field.value.len() - 1,
+            };
+            output[at] |= 0x80;
+        }
+        // Dropped field
+        3 => {}
+        // Duplicated field
+        4 => {
+            field.write(output);
+            field.write(output);
+        }
+        // Unknown field
+        _ => {
+            Field {
+                number: MAX_FIELD_NUMBER,
+                wire_type: WireType::Varint,
+                value: &[0],
+            }
+            .write(output);
+            field.write(output);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::CountingGarbler;
+
+    /// Field 1 with the varint 150, field 2 with the string "hi", and field 3
+    /// with a nested message containing field 1 with the fixed32 1
+    const MESSAGE: [u8; 14] = [
+        0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i', 0x1a, 0x05, 0x0d, 0x01, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_parse() {
+        // GIVEN an encoded message
+        let mut bytes = &MESSAGE[..];
+        // WHEN we parse its fields
+        let mut fields = Vec::new();
+        while let Some((field, rest)) = Field::parse(bytes) {
+            fields.push(field);
+            bytes = rest;
+        }
+        // THEN each field should be parsed
+        assert!(bytes.is_empty());
+        assert_eq!(
+            fields,
+            vec![
+                Field {
+                    number: 1,
+                    wire_type: WireType::Varint,
+                    value: &[0x96, 0x01]
+                },
+                Field {
+                    number: 2,
+                    wire_type: WireType::Len,
+                    value: b"hi"
+                },
+                Field {
+                    number: 3,
+                    wire_type: WireType::Len,
+                    value: &[0x0d, 0x01, 0x00, 0x00, 0x00]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        // GIVEN length-delimited values
+        // WHEN we classify them
+        // THEN they should be recognized as messages, packed varints, text or
+        // bytes
+        assert_eq!(Payload::classify(&MESSAGE), Payload::Message);
+        assert_eq!(
+            Payload::classify(&[0x01, 0x02, 0xac, 0x02]),
+            Payload::Packed
+        );
+        assert_eq!(Payload::classify(b"hello"), Payload::Text("hello"));
+        assert_eq!(Payload::classify(b""), Payload::Message);
+        assert_eq!(
+            Payload::classify(&[0xff, 0x00]),
+            Payload::Bytes(&[0xff, 0x00])
+        );
+    }
+
+    #[test]
+    fn test_varint() {
+        // GIVEN values
+        for value in [0, 1, 150, u32::MAX as u64, u64::MAX] {
+            // WHEN we write and read them as varints
+            let mut bytes = Vec::new();
+            write_varint(value, &mut bytes);
+            // THEN they should be the same
+            assert_eq!(read_varint(&bytes), Some((value, &[][..])));
+        }
+        // AND unterminated varints should not be read
+        assert_eq!(read_varint(&[0x80]), None);
+    }
+
+    #[test]
+    fn test_fields_passthrough() {
+        // GIVEN an encoded message
+        // WHEN we garble its fields with a garbler that does not change anything
+        let mut garbler = CountingGarbler::new();
+        let garbled = garble_fields(MESSAGE, &mut garbler);
+        // THEN the message should be unchanged
+        assert_eq!(garbled, MESSAGE);
+        // AND the garbler should see the varint, the text and the fixed32
+        assert_eq!(garbler.count(), 3);
+    }
+
+    #[test]
+    fn test_packed_passthrough() {
+        // GIVEN field 4 with the packed varints 1, 2 and 300
+        let packed = [0x22, 0x04, 0x01, 0x02, 0xac, 0x02];
+        // WHEN we garble its fields with a garbler that does not change anything
+        let mut garbler = CountingGarbler::new();
+        let garbled = garble_fields(packed, &mut garbler);
+        // THEN the message should be unchanged
+        assert_eq!(garbled, packed);
+        // AND the garbler should see each varint
+        assert_eq!(garbler.count(), 3);
+    }
+
+    #[cfg(feature = "simple")]
+    #[test]
+    fn test_packed_valid() {
+        use crate::SimpleGarbler;
+
+        // GIVEN field 4 with the packed varints 1, 2 and 300
+        let packed = [0x22, 0x04, 0x01, 0x02, 0xac, 0x02];
+        let mut garbler = SimpleGarbler::new(1.0).unwrap();
+        for _ in 0..32 {
+            // WHEN we garble its fields
+            let garbled = garble_fields(packed, &mut garbler);
+            // THEN the value should still be packed varints
+            let (field, rest) = Field::parse(&garbled).unwrap();
+            assert!(rest.is_empty());
+            assert!(is_packed(field.value), "{garbled:?}");
+        }
+    }
+
+    #[test]
+    fn test_wire_passthrough() {
+        // GIVEN an encoded message
+        // WHEN we garble it with a garbler that never applies structural faults
+        let garbled = garble_wire(MESSAGE, &mut CountingGarbler::new());
+        // THEN the message should be unchanged
+        assert_eq!(garbled, MESSAGE);
+    }
+
+    #[test]
+    fn test_faults() {
+        // GIVEN a field with the varint 150
+        let (field, _) = Field::parse(&MESSAGE).unwrap();
+        let cases: [(usize, &[u8]); 6] = [
+            (0, &[0x00, 0x96, 0x01]),
+            (1, &[0x09, 0x96, 0x01]),
+            (2, &[0x08, 0x96, 0x81]),
+            (3, &[]),
+            (4, &[0x08, 0x96, 0x01, 0x08, 0x96, 0x01]),
+            (5, &[0xf8, 0xff, 0xff, 0xff, 0x0f, 0x00, 0x08, 0x96, 0x01]),
+        ];
+        for (fault, expected) in cases {
+            // WHEN we apply the fault
+            let mut output = Vec::new();
+            apply_fault(field, fault, &mut output);
+            // THEN the field should be garbled
+            assert_eq!(output, expected, "fault {fault}");
+        }
+    }
+
+    #[test]
+    fn test_unterminated_tag() {
+        // GIVEN a fixed32 field
+        let (field, _) = Field::parse(&MESSAGE[9..]).unwrap();
+        // WHEN we apply an unterminated varint
+        let mut output = Vec::new();
+        apply_fault(field, 2, &mut output);
+        // THEN the tag should run into the value
+        assert_eq!(output, [0x8d, 0x01, 0x00, 0x00, 0x00]);
+    }
+}
@@ -12,3 +12,14 @@ pub trait Garble: Sized {
     where
         G: Garbler;
 }
+
+/// Trait for shared values that can be garbled in place
+///
+/// This is useful to corrupt live shared state, such as values behind a lock,
+/// without having to take ownership of them.
+pub trait GarbleInPlace {
+    /// Garble the data in place with the given garbler
+    fn garble_in_place<G>(&self, garbler: &mut G)
+    where
+        G: Garbler;
+}
@@ -0,0 +1,162 @@
+use super::DynGarbler;
+use crate::Garbler;
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    ffi::CString,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+use paste::paste;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// [`Garbler`] that applies several garblers in sequence
+///
+/// Each primitive value is passed through every inner garbler, in the order
+/// they were added, with the output of one garbler being the input of the
+/// next.
+#[derive(Default)]
+pub struct ChainGarbler<'a> {
+    garblers: Vec<Box<dyn DynGarbler + 'a>>,
+}
+
+impl<'a> ChainGarbler<'a> {
+    /// Create a new, empty [`ChainGarbler`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a garbler at the end of the chain
+    pub fn with<G>(mut self, garbler: G) -> Self
+    where
+        G: Garbler + 'a,
+    {
+        self.garblers.push(Box::new(garbler));
+        self
+    }
+}
+
+impl fmt::Debug for ChainGarbler<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainGarbler")
+            .field("len", &self.garblers.len())
+            .finish()
+    }
+}
+
+macro_rules! impl_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t {
+                    self.garblers
+                        .iter_mut()
+                        .fold(value, |value, garbler| garbler.[<garble_ $t:snake>](value))
+                }
+            }
+        )*
+    };
+}
+
+impl Garbler for ChainGarbler<'_> {
+    impl_func! { bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6 }
+
+    fn garble_str<T>(&mut self, value: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        self.garblers
+            .iter_mut()
+            .fold(value.as_ref().to_string(), |value, garbler| {
+                garbler.garble_str(&value)
+            })
+    }
+
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        self.garblers
+            .iter_mut()
+            .fold(value.as_ref().to_owned(), |value, garbler| {
+                garbler.garble_c_str(&value)
+            })
+    }
+
+    fn structural_fault(&mut self, faults: usize) -> Option<usize> {
+        self.garblers
+            .iter_mut()
+            .find_map(|garbler| garbler.structural_fault(faults))
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn garble_json_structure(&mut self, value: serde_json::Value) -> serde_json::Value {
+        self.garblers
+            .iter_mut()
+            .fold(value, |value, garbler| garbler.garble_json_structure(value))
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        self.garblers
+            .iter_mut()
+            .fold(value.as_ref().to_os_string(), |value, garbler| {
+                garbler.garble_os_str(&value)
+            })
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        self.garblers
+            .iter_mut()
+            .fold(value.as_ref().to_path_buf(), |value, garbler| {
+                garbler.garble_path(&value)
+            })
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "simple"))]
+mod tests {
+    use super::*;
+    use crate::{Garble, SimpleGarbler};
+
+    #[test]
+    fn test_empty() {
+        // GIVEN an empty ChainGarbler
+        let mut garbler = ChainGarbler::new();
+        // WHEN we garble a value
+        let value = (true, 1u32, "hello").garble(&mut garbler);
+        // THEN the value should be the same as the original
+        assert_eq!(value, (true, 1u32, "hello".to_string()));
+    }
+
+    #[test]
+    fn test_chain() {
+        // GIVEN a ChainGarbler flipping booleans twice
+        let mut garbler = ChainGarbler::new()
+            .with(SimpleGarbler::new(1.0).unwrap())
+            .with(SimpleGarbler::new(1.0).unwrap());
+        // WHEN we garble a boolean
+        let value = true.garble(&mut garbler);
+        // THEN the value should be flipped back
+        assert!(value);
+    }
+}
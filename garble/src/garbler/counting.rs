@@ -0,0 +1,129 @@
+use crate::{Garble, Garbler};
+use alloc::{
+    borrow::ToOwned,
+    ffi::CString,
+    string::{String, ToString},
+};
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+use paste::paste;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// [`Garbler`] that leaves data untouched and counts garblable leaves
+///
+/// Every call to one of the primitive `garble_*` functions counts as one leaf.
+/// This is useful as a dry-run to know how many values a garbler will be
+/// asked to modify when garbling a given value.
+#[derive(Debug, Default)]
+pub struct CountingGarbler {
+    count: usize,
+}
+
+impl CountingGarbler {
+    /// Create a new [`CountingGarbler`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of leaves seen so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Count the garblable leaves of a value
+    pub fn count_leaves<T>(value: &T) -> usize
+    where
+        T: Garble + Clone,
+    {
+        let mut garbler = Self::new();
+        value.clone().garble(&mut garbler);
+        garbler.count()
+    }
+}
+
+macro_rules! impl_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t {
+                    self.count += 1;
+                    value
+                }
+            }
+        )*
+    };
+}
+
+impl Garbler for CountingGarbler {
+    impl_func! { bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6 }
+
+    fn garble_str<T>(&mut self, value: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        self.count += 1;
+        value.as_ref().to_string()
+    }
+
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        self.count += 1;
+        value.as_ref().to_owned()
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        self.count += 1;
+        value.as_ref().to_os_string()
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        self.count += 1;
+        value.as_ref().to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_count_leaves() {
+        // GIVEN a value with 4 leaves
+        let value = (1u32, 'a', vec![true, false]);
+        // WHEN we count its leaves
+        let count = CountingGarbler::count_leaves(&value);
+        // THEN the count should be 4
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn test_passthrough() {
+        // GIVEN a CountingGarbler
+        let mut garbler = CountingGarbler::new();
+        // WHEN we garble a value
+        let value = (1u32, "hello").garble(&mut garbler);
+        // THEN the value should be the same as the original
+        assert_eq!(value, (1u32, "hello".to_string()));
+        assert_eq!(garbler.count(), 2);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::Garbler;
+use alloc::{ffi::CString, string::String};
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+use paste::paste;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+macro_rules! dyn_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t;
+            }
+        )*
+    };
+}
+
+/// Object-safe version of [`Garbler`]
+///
+/// [`Garbler`] cannot be used as a trait object due to its generic functions.
+/// This trait is implemented for all garblers, which allows storing garblers
+/// of different types together, such as in a [`ChainGarbler`](crate::ChainGarbler).
+#[allow(missing_docs)]
+pub trait DynGarbler {
+    dyn_func! {
+        bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64,
+        Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr,
+        Ipv6Addr, SocketAddrV4, SocketAddrV6
+    }
+
+    fn garble_str(&mut self, value: &str) -> String;
+
+    fn garble_c_str(&mut self, value: &CStr) -> CString;
+
+    fn structural_fault(&mut self, faults: usize) -> Option<usize>;
+
+    #[cfg(feature = "serde_json")]
+    fn garble_json_structure(&mut self, value: serde_json::Value) -> serde_json::Value;
+
+    #[cfg(feature = "std")]
+    fn garble_os_str(&mut self, value: &OsStr) -> OsString;
+
+    #[cfg(feature = "std")]
+    fn garble_path(&mut self, value: &Path) -> PathBuf;
+}
+
+macro_rules! impl_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t {
+                    Garbler::[<garble_ $t:snake>](self, value)
+                }
+            }
+        )*
+    };
+}
+
+impl<G> DynGarbler for G
+where
+    G: Garbler,
+{
+    impl_func! { bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6 }
+
+    fn garble_str(&mut self, value: &str) -> String {
+        Garbler::garble_str(self, value)
+    }
+
+    fn garble_c_str(&mut self, value: &CStr) -> CString {
+        Garbler::garble_c_str(self, value)
+    }
+
+    fn structural_fault(&mut self, faults: usize) -> Option<usize> {
+        Garbler::structural_fault(self, faults)
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn garble_json_structure(&mut self, value: serde_json::Value) -> serde_json::Value {
+        Garbler::garble_json_structure(self, value)
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_os_str(&mut self, value: &OsStr) -> OsString {
+        Garbler::garble_os_str(self, value)
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_path(&mut self, value: &Path) -> PathBuf {
+        Garbler::garble_path(self, value)
+    }
+}
@@ -1,15 +1,51 @@
-use crate::Garble;
+use crate::{shrink, Garble, Shrunk};
+use alloc::{ffi::CString, string::String, vec::Vec};
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
 use paste::paste;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{self, Instant, SystemTime},
+};
+
+mod chain;
+pub use chain::ChainGarbler;
+
+mod counting;
+pub use counting::CountingGarbler;
+
+mod dynamic;
+pub use dynamic::DynGarbler;
 
 #[cfg(feature = "simple")]
 mod simple;
 #[cfg(feature = "simple")]
-pub use simple::SimpleGarbler;
+pub use simple::{SimpleGarbler, SimpleGarblerBuilder};
+
+mod recording;
+pub use recording::RecordingGarbler;
+
+mod replay;
+pub use replay::ReplayGarbler;
+
+mod targeted;
+pub use targeted::{SingleFaults, TargetedGarbler};
+
+#[cfg(feature = "simple")]
+mod weighted;
+#[cfg(feature = "simple")]
+pub use weighted::WeightedGarbler;
 
 macro_rules! garble_func {
-    ($($t:ty),*) => {
+    ($($(#[$meta:meta])* $t:ty),*) => {
         $(
             paste! {
+                $(#[$meta])*
                 fn [<garble_ $t:lower>](&mut self, value: $t) -> $t;
             }
         )*
@@ -29,6 +65,30 @@ pub trait Garbler: Sized {
         value.garble(self)
     }
 
+    /// Enumerate all single-leaf faults of a value
+    ///
+    /// This returns an iterator yielding one garbled copy of the value per
+    /// leaf, where only that leaf is garbled by this `Garbler`. Leaves are
+    /// counted with a dry-run using a [`CountingGarbler`].
+    fn single_faults<T>(self, value: T) -> SingleFaults<T, Self>
+    where
+        T: Garble + Clone,
+    {
+        SingleFaults::new(value, self)
+    }
+
+    /// Garble a value and shrink the mutations to a minimal failing set
+    ///
+    /// The predicate returns `true` when the garbled value makes the test
+    /// fail. See [`shrink`](crate::shrink) for more details.
+    fn shrink<T, F>(self, value: T, predicate: F) -> Option<Shrunk<T::Output>>
+    where
+        T: Garble + Clone,
+        F: FnMut(&T::Output) -> bool,
+    {
+        shrink::shrink(value, self, predicate)
+    }
+
     garble_func!(
         // Other types
         bool, char, // Unsigned integers
@@ -40,4 +100,275 @@ pub trait Garbler: Sized {
     fn garble_str<T>(&mut self, value: T) -> String
     where
         T: AsRef<str>;
+
+    /// Garble a C string
+    ///
+    /// By default, this garbles each byte independently. As C strings cannot
+    /// contain nul bytes, these are replaced by question marks.
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        let bytes = value
+            .as_ref()
+            .to_bytes()
+            .iter()
+            .map(|b| match self.garble_u8(*b) {
+                0 => b'?',
+                b => b,
+            })
+            .collect::<Vec<_>>();
+        CString::new(bytes).unwrap()
+    }
+
+    #[cfg(feature = "std")]
+    /// Garble an OS string
+    ///
+    /// By default, this garbles valid UTF-8 strings with [`garble_str`](Self::garble_str)
+    /// and leaves other strings untouched.
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        let value = value.as_ref();
+        match value.to_str() {
+            Some(s) => self.garble_str(s).into(),
+            None => value.to_os_string(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    /// Garble a path
+    ///
+    /// By default, this garbles the path as an OS string with
+    /// [`garble_os_str`](Self::garble_os_str).
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        self.garble_os_str(value.as_ref().as_os_str()).into()
+    }
+
+    /// Garble the bounds of a range
+    ///
+    /// By default, this garbles both bounds independently, then inverts the
+    /// range or makes it empty if [`structural_fault`](Self::structural_fault)
+    /// picks one of these faults.
+    fn garble_range<T>(&mut self, start: T, end: T) -> (T::Output, T::Output)
+    where
+        T: Garble,
+        T::Output: Clone,
+    {
+        let (start, end) = (start.garble(self), end.garble(self));
+        match self.structural_fault(2) {
+            // Inverted range
+            Some(0) => (end, start),
+            // Empty range
+            Some(_) => (start.clone(), start),
+            None => (start, end),
+        }
+    }
+
+    /// Garble the bounds of an inclusive range
+    ///
+    /// This works like [`garble_range`](Self::garble_range), except that
+    /// inclusive ranges with equal bounds still contain one value, so empty
+    /// ranges are made by swapping the bounds instead. Ranges whose bounds are
+    /// already equal or unordered after garbling are kept as-is.
+    fn garble_range_inclusive<T>(&mut self, start: T, end: T) -> (T::Output, T::Output)
+    where
+        T: Garble,
+        T::Output: PartialOrd,
+    {
+        let (start, end) = (start.garble(self), end.garble(self));
+        match self.structural_fault(2) {
+            // Inverted range
+            Some(0) => (end, start),
+            // Empty range
+            Some(_) if start < end => (end, start),
+            _ => (start, end),
+        }
+    }
+
+    /// Pick a structural fault to apply
+    ///
+    /// This is called by ranges and by format-level garblers, such as the
+    /// ones in [`formats`](crate::formats), at every position where one of
+    /// `faults` different faults could be applied. It returns the index of the fault to
+    /// apply, or `None` to leave the structure untouched. By default, this
+    /// never applies structural faults.
+    fn structural_fault(&mut self, faults: usize) -> Option<usize> {
+        let _ = faults;
+        None
+    }
+
+    /// Apply structural faults to a JSON value
+    ///
+    /// This is called for every node of a [`serde_json::Value`] after its
+    /// children were garbled. By default, this leaves the value untouched, as
+    /// numbers, strings and booleans are already garbled as primitive values.
+    /// Implementations can override this to produce JSON-specific faults, such
+    /// as removing keys or injecting nulls.
+    #[cfg(feature = "serde_json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde_json")))]
+    fn garble_json_structure(&mut self, value: serde_json::Value) -> serde_json::Value {
+        value
+    }
+
+    /// Garble an IPv4 address
+    ///
+    /// By default, this garbles each octet independently.
+    fn garble_ipv4_addr(&mut self, value: Ipv4Addr) -> Ipv4Addr {
+        value.octets().garble(self).into()
+    }
+
+    /// Garble an IPv6 address
+    ///
+    /// By default, this garbles each segment independently.
+    fn garble_ipv6_addr(&mut self, value: Ipv6Addr) -> Ipv6Addr {
+        value.segments().garble(self).into()
+    }
+
+    /// Garble an IPv4 socket address
+    ///
+    /// By default, this garbles the IP address and the port independently.
+    fn garble_socket_addr_v4(&mut self, value: SocketAddrV4) -> SocketAddrV4 {
+        SocketAddrV4::new(
+            self.garble_ipv4_addr(*value.ip()),
+            self.garble_u16(value.port()),
+        )
+    }
+
+    /// Garble an IPv6 socket address
+    ///
+    /// By default, this garbles the IP address, port, flow information and
+    /// scope ID independently.
+    fn garble_socket_addr_v6(&mut self, value: SocketAddrV6) -> SocketAddrV6 {
+        SocketAddrV6::new(
+            self.garble_ipv6_addr(*value.ip()),
+            self.garble_u16(value.port()),
+            self.garble_u32(value.flowinfo()),
+            self.garble_u32(value.scope_id()),
+        )
+    }
+
+    /// Garble a duration
+    ///
+    /// By default, this garbles the total number of nanoseconds as a single
+    /// `u128`, so that a duration is one leaf for every garbler. Values that
+    /// do not fit in a duration saturate to [`Duration::MAX`].
+    fn garble_duration(&mut self, value: Duration) -> Duration {
+        const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+        let nanos = self.garble_u128(value.as_nanos());
+        match u64::try_from(nanos / NANOS_PER_SEC) {
+            Ok(secs) => Duration::new(secs, (nanos % NANOS_PER_SEC) as u32),
+            Err(_) => Duration::MAX,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    /// Garble a system time
+    ///
+    /// By default, this garbles the duration between the time and the Unix
+    /// epoch, keeping times before the epoch before it. If the resulting time
+    /// cannot be represented, the original value is returned.
+    fn garble_system_time(&mut self, value: SystemTime) -> SystemTime {
+        match value.duration_since(time::UNIX_EPOCH) {
+            Ok(offset) => time::UNIX_EPOCH.checked_add(self.garble_duration(offset)),
+            Err(err) => time::UNIX_EPOCH.checked_sub(self.garble_duration(err.duration())),
+        }
+        .unwrap_or(value)
+    }
+
+    #[cfg(feature = "std")]
+    /// Garble an instant
+    ///
+    /// Instants are opaque, so by default this garbles a zero offset and adds
+    /// it to the instant. If the resulting instant cannot be represented, the
+    /// original value is returned.
+    fn garble_instant(&mut self, value: Instant) -> Instant {
+        value
+            .checked_add(self.garble_duration(Duration::ZERO))
+            .unwrap_or(value)
+    }
+}
+
+macro_rules! forward_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t {
+                    (**self).[<garble_ $t:snake>](value)
+                }
+            }
+        )*
+    }
+}
+
+impl<G> Garbler for &mut G
+where
+    G: Garbler,
+{
+    forward_func! {
+        bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64,
+        Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr,
+        Ipv6Addr, SocketAddrV4, SocketAddrV6
+    }
+
+    fn garble_str<T>(&mut self, value: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        (**self).garble_str(value)
+    }
+
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        (**self).garble_c_str(value)
+    }
+
+    fn garble_range<T>(&mut self, start: T, end: T) -> (T::Output, T::Output)
+    where
+        T: Garble,
+        T::Output: Clone,
+    {
+        (**self).garble_range(start, end)
+    }
+
+    fn garble_range_inclusive<T>(&mut self, start: T, end: T) -> (T::Output, T::Output)
+    where
+        T: Garble,
+        T::Output: PartialOrd,
+    {
+        (**self).garble_range_inclusive(start, end)
+    }
+
+    fn structural_fault(&mut self, faults: usize) -> Option<usize> {
+        (**self).structural_fault(faults)
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn garble_json_structure(&mut self, value: serde_json::Value) -> serde_json::Value {
+        (**self).garble_json_structure(value)
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        (**self).garble_os_str(value)
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        (**self).garble_path(value)
+    }
 }
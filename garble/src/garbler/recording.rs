@@ -0,0 +1,140 @@
+use crate::{Garbler, Mutation, Primitive};
+use alloc::{
+    borrow::ToOwned,
+    ffi::CString,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+use paste::paste;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// [`Garbler`] that records the mutations applied by an inner garbler
+///
+/// Only leaves that were actually modified by the inner garbler are recorded.
+/// Structural faults picked by the inner garbler, such as inverted ranges, are
+/// applied but not recorded.
+/// The resulting log can be replayed with a [`ReplayGarbler`](crate::ReplayGarbler).
+#[derive(Debug)]
+pub struct RecordingGarbler<G> {
+    inner: G,
+    index: usize,
+    log: Vec<Mutation>,
+}
+
+impl<G> RecordingGarbler<G>
+where
+    G: Garbler,
+{
+    /// Create a new [`RecordingGarbler`] around the inner garbler
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            index: 0,
+            log: Vec::new(),
+        }
+    }
+
+    /// Mutations recorded so far
+    pub fn log(&self) -> &[Mutation] {
+        &self.log
+    }
+
+    /// Consume this garbler and return the recorded mutations
+    pub fn into_log(self) -> Vec<Mutation> {
+        self.log
+    }
+
+    fn record(&mut self, original: Primitive, garbled: Primitive) {
+        if !original.is_identical(&garbled) {
+            self.log.push(Mutation {
+                index: self.index,
+                original,
+                garbled,
+            });
+        }
+        self.index += 1;
+    }
+}
+
+macro_rules! impl_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t {
+                    let garbled = self.inner.[<garble_ $t:snake>](value);
+                    self.record(value.into(), garbled.into());
+                    garbled
+                }
+            }
+        )*
+    };
+}
+
+impl<G> Garbler for RecordingGarbler<G>
+where
+    G: Garbler,
+{
+    impl_func! { bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6 }
+
+    fn garble_str<T>(&mut self, value: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        let original = value.as_ref().to_string();
+        let garbled = self.inner.garble_str(value);
+        self.record(original.into(), garbled.clone().into());
+        garbled
+    }
+
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        let original = value.as_ref().to_owned();
+        let garbled = self.inner.garble_c_str(value);
+        self.record(original.into(), garbled.clone().into());
+        garbled
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        let original = value.as_ref().to_os_string();
+        let garbled = self.inner.garble_os_str(value);
+        self.record(original.into(), garbled.clone().into());
+        garbled
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        let original = value.as_ref().to_path_buf();
+        let garbled = self.inner.garble_path(value);
+        self.record(original.into(), garbled.clone().into());
+        garbled
+    }
+
+    fn structural_fault(&mut self, faults: usize) -> Option<usize> {
+        self.inner.structural_fault(faults)
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn garble_json_structure(&mut self, value: serde_json::Value) -> serde_json::Value {
+        self.inner.garble_json_structure(value)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{Garbler, Mutation, Primitive};
+use alloc::{
+    borrow::ToOwned,
+    ffi::CString,
+    string::{String, ToString},
+};
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+use paste::paste;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// [`Garbler`] that replays a log of mutations
+///
+/// Leaves are counted in the order the primitive `garble_*` functions are
+/// called. If a mutation was recorded for the current leaf and its type
+/// matches, the garbled value is returned. Otherwise, the value is left
+/// untouched.
+#[derive(Debug)]
+pub struct ReplayGarbler<'a> {
+    log: &'a [Mutation],
+    index: usize,
+}
+
+impl<'a> ReplayGarbler<'a> {
+    /// Create a new [`ReplayGarbler`] from a log of mutations
+    ///
+    /// The mutations must be sorted by index, as produced by a
+    /// [`RecordingGarbler`](crate::RecordingGarbler).
+    pub fn new(log: &'a [Mutation]) -> Self {
+        Self { log, index: 0 }
+    }
+
+    fn next_mutation(&mut self) -> Option<&'a Primitive> {
+        let index = self.index;
+        self.index += 1;
+
+        while let Some((mutation, rest)) = self.log.split_first() {
+            if mutation.index > index {
+                return None;
+            }
+            self.log = rest;
+            if mutation.index == index {
+                return Some(&mutation.garbled);
+            }
+        }
+        None
+    }
+}
+
+macro_rules! impl_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t {
+                    match self.next_mutation() {
+                        Some(Primitive::[<$t:camel>](garbled)) => garbled.clone(),
+                        _ => value,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl Garbler for ReplayGarbler<'_> {
+    impl_func! { bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6 }
+
+    fn garble_str<T>(&mut self, value: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        match self.next_mutation() {
+            Some(Primitive::Str(garbled)) => garbled.clone(),
+            _ => value.as_ref().to_string(),
+        }
+    }
+
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        match self.next_mutation() {
+            Some(Primitive::CStr(garbled)) => garbled.clone(),
+            _ => value.as_ref().to_owned(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        match self.next_mutation() {
+            Some(Primitive::OsStr(garbled)) => garbled.clone(),
+            _ => value.as_ref().to_os_string(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        match self.next_mutation() {
+            Some(Primitive::Path(garbled)) => garbled.clone(),
+            _ => value.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "simple"))]
+mod tests {
+    use crate::{Garble, RecordingGarbler, ReplayGarbler, SimpleGarbler};
+
+    #[test]
+    fn test_replay() {
+        // GIVEN a log of mutations recorded on a value
+        let value = (1u32, "hello", [true, false], 'c');
+        let mut garbler = RecordingGarbler::new(SimpleGarbler::new(0.5).unwrap());
+        let garbled = value.garble(&mut garbler);
+        let log = garbler.into_log();
+        // WHEN we replay the log on the same value
+        let replayed = value.garble(&mut ReplayGarbler::new(&log));
+        // THEN the value should be garbled identically
+        assert_eq!(replayed, garbled);
+    }
+
+    #[test]
+    fn test_replay_empty() {
+        // GIVEN an empty log of mutations
+        let value = (1u32, "hello");
+        // WHEN we replay the log
+        let replayed = value.garble(&mut ReplayGarbler::new(&[]));
+        // THEN the value should be the same as the original
+        assert_eq!(replayed, (1u32, "hello".to_string()));
+    }
+}
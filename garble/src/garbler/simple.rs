@@ -1,28 +1,379 @@
-use crate::Garbler;
+use crate::{error::check_rate, Error, Garbler, PrimitiveKind, PrimitiveType};
+use alloc::{ffi::CString, string::String, vec::Vec};
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
 use paste::paste;
 use rand::prelude::*;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{self, Path, PathBuf},
+    time::{self, Instant, SystemTime},
+};
+
+/// Number of seconds in a (non-leap) year
+const YEAR: u64 = 365 * 24 * 60 * 60;
+/// Number of nanoseconds in a second
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+/// Maximum length of a file name on most file systems
+#[cfg(feature = "std")]
+const NAME_MAX: usize = 255;
+/// Default length of padded strings
+const PAD_STR_LEN: usize = 64 * 1024;
+/// Default maximum length of garbled strings
+const MAX_STR_LEN: usize = 1024 * 1024;
+/// Kinds of integer primitives
+const INTEGERS: [PrimitiveKind; 12] = [
+    PrimitiveKind::U8,
+    PrimitiveKind::U16,
+    PrimitiveKind::U32,
+    PrimitiveKind::U64,
+    PrimitiveKind::U128,
+    PrimitiveKind::Usize,
+    PrimitiveKind::I8,
+    PrimitiveKind::I16,
+    PrimitiveKind::I32,
+    PrimitiveKind::I64,
+    PrimitiveKind::I128,
+    PrimitiveKind::Isize,
+];
+/// Kinds of floating point primitives
+const FLOATS: [PrimitiveKind; 2] = [PrimitiveKind::F32, PrimitiveKind::F64];
 
 /// Simple implement of a randomizer [`Garbler`]
 ///
 /// This will garble data randomly based on the given rate.
+///
+/// With the `std` feature, this uses the thread-local random number generator
+/// by default. Without it, a random number generator must be provided with
+/// [`with_rng`](Self::with_rng) or [`SimpleGarblerBuilder::build_with_rng`].
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
 #[derive(Debug)]
-pub struct SimpleGarbler {
-    rate: f64,
-    rng: ThreadRng,
+pub struct SimpleGarbler<#[cfg(feature = "std")] R = ThreadRng, #[cfg(not(feature = "std"))] R> {
+    rates: [f64; PrimitiveKind::COUNT],
+    disabled: [bool; PrimitiveKind::COUNT],
+    structural_rate: f64,
+    pad_str_len: usize,
+    max_str_len: usize,
+    #[cfg(feature = "serde_json")]
+    json_nesting_depth: usize,
+    rng: R,
 }
 
+#[cfg(feature = "std")]
 impl SimpleGarbler {
     /// Create a new [`SimpleGarbler`] with the given rate
-    pub fn new(rate: f64) -> Self {
+    ///
+    /// This is equivalent to `SimpleGarbler::builder().rate(rate).build()`.
+    ///
+    /// Returns an [`Error::InvalidRate`] if the rate is not between 0.0 and
+    /// 1.0.
+    pub fn new(rate: f64) -> Result<Self, Error> {
+        Self::builder().rate(rate).build()
+    }
+
+    /// Create a [`SimpleGarblerBuilder`] to configure rates per type
+    ///
+    /// Types without a specific rate use the default rate, which is 0.5 unless
+    /// changed with [`SimpleGarblerBuilder::rate`].
+    pub fn builder() -> SimpleGarblerBuilder {
+        SimpleGarblerBuilder::default()
+    }
+}
+
+impl<R> SimpleGarbler<R>
+where
+    R: Rng,
+{
+    /// Create a new [`SimpleGarbler`] with the given rate and random number
+    /// generator
+    ///
+    /// Returns an [`Error::InvalidRate`] if the rate is not between 0.0 and
+    /// 1.0.
+    pub fn with_rng(rate: f64, rng: R) -> Result<Self, Error> {
+        SimpleGarblerBuilder::default()
+            .rate(rate)
+            .build_with_rng(rng)
+    }
+
+    fn should_garble(&mut self, kind: PrimitiveKind) -> bool {
+        self.rng.gen_bool(self.rates[kind as usize])
+    }
+
+    fn should_garble_structure(&mut self) -> bool {
+        self.rng.gen_bool(self.structural_rate)
+    }
+
+    /// Garble each character of a string with the rate of the given kind
+    fn garble_chars(&mut self, value: &str, kind: PrimitiveKind) -> String {
+        value
+            .chars()
+            .map(|c| {
+                if self.should_garble(kind) {
+                    self.rng.gen()
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+
+    /// Change the length of a string-like value
+    ///
+    /// Length faults are structural faults, but are never applied if the kind
+    /// of value is disabled. Strings never grow past the maximum length, but
+    /// are not truncated if they were already longer than it.
+    fn garble_len<T, F>(&mut self, mut items: Vec<T>, kind: PrimitiveKind, mut gen: F) -> Vec<T>
+    where
+        F: FnMut(&mut R) -> T,
+    {
+        if self.disabled[kind as usize] || !self.should_garble_structure() {
+            return items;
+        }
+
+        let len = items.len();
+        let max_len = self.max_str_len.max(len);
+        let new_len = match self.rng.gen_range(0..4) {
+            // Grow
+            0 => len.saturating_add(self.rng.gen_range(1..=len.max(16))),
+            // Shrink
+            1 if len > 0 => self.rng.gen_range(0..len),
+            // Pad to a huge size
+            2 => self.pad_str_len.max(len + 1),
+            // Truncate to empty
+            _ => 0,
+        }
+        .min(max_len);
+
+        items.truncate(new_len);
+        while items.len() < new_len {
+            items.push(gen(&mut self.rng));
+        }
+        items
+    }
+
+    /// Random port, which can be zero, privileged, ephemeral or any other
+    fn port(&mut self) -> u16 {
+        match self.rng.gen_range(0..4) {
+            0 => 0,
+            1 => self.rng.gen_range(1..1024),
+            2 => self.rng.gen_range(49152..=u16::MAX),
+            _ => self.rng.gen(),
+        }
+    }
+
+    /// Random clock skew between one millisecond and `max`
+    #[cfg(feature = "std")]
+    fn skew(&mut self, max: Duration) -> Duration {
+        let millis = self.rng.gen_range(1..=max.as_millis().max(1) as u64);
+        Duration::from_millis(millis)
+    }
+}
+
+/// Builder for a [`SimpleGarbler`]
+///
+/// Types without a specific rate use the default rate, which is 0.5 unless
+/// changed with [`rate`](Self::rate).
+///
+/// Without the `std` feature, [`SimpleGarbler::builder`] is not available:
+/// create the builder with [`SimpleGarblerBuilder::default`] instead, and
+/// finish it with [`build_with_rng`](Self::build_with_rng).
+///
+/// ```rust
+/// use garble::SimpleGarbler;
+///
+/// let garbler = SimpleGarbler::builder()
+///     .rate(0.1)
+///     .rate_for::<bool>(0.5)
+///     .rate_for_str(0.01)
+///     .disable::<f64>()
+///     .build()
+///     .unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+#[derive(Clone, Debug)]
+pub struct SimpleGarblerBuilder {
+    rate: f64,
+    rates: [Option<f64>; PrimitiveKind::COUNT],
+    disabled: [bool; PrimitiveKind::COUNT],
+    structural_rate: f64,
+    pad_str_len: usize,
+    max_str_len: usize,
+    #[cfg(feature = "serde_json")]
+    json_nesting_depth: usize,
+}
+
+impl Default for SimpleGarblerBuilder {
+    fn default() -> Self {
         Self {
-            rate,
-            rng: rand::thread_rng(),
+            rate: 0.5,
+            rates: [None; PrimitiveKind::COUNT],
+            disabled: [false; PrimitiveKind::COUNT],
+            structural_rate: 0.0,
+            pad_str_len: PAD_STR_LEN,
+            max_str_len: MAX_STR_LEN,
+            #[cfg(feature = "serde_json")]
+            json_nesting_depth: 0,
         }
     }
+}
+
+impl SimpleGarblerBuilder {
+    /// Set the default rate for all types
+    ///
+    /// Defaults to 0.5.
+    pub fn rate(mut self, rate: f64) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Set the rate for a specific primitive type
+    pub fn rate_for<T>(self, rate: f64) -> Self
+    where
+        T: PrimitiveType + ?Sized,
+    {
+        self.set_rate(T::KIND, rate)
+    }
+
+    /// Set the rate for each character in strings
+    pub fn rate_for_str(self, rate: f64) -> Self {
+        self.set_rate(PrimitiveKind::Str, rate)
+    }
+
+    /// Set the rate for all integer types
+    pub fn rate_for_integers(self, rate: f64) -> Self {
+        INTEGERS
+            .into_iter()
+            .fold(self, |builder, kind| builder.set_rate(kind, rate))
+    }
+
+    /// Set the rate for all floating point types
+    pub fn rate_for_floats(self, rate: f64) -> Self {
+        FLOATS
+            .into_iter()
+            .fold(self, |builder, kind| builder.set_rate(kind, rate))
+    }
 
-    fn should_garble(&mut self) -> bool {
-        self.rng.gen_bool(self.rate)
+    /// Never garble a specific primitive type
+    ///
+    /// Unlike a rate of 0.0, this also prevents structural faults on values
+    /// of this type, such as changing the length of a string.
+    pub fn disable<T>(self) -> Self
+    where
+        T: PrimitiveType + ?Sized,
+    {
+        self.set_disabled(T::KIND)
+    }
+
+    /// Never garble strings
+    ///
+    /// Unlike a rate of 0.0, this also prevents changing their length.
+    pub fn disable_str(self) -> Self {
+        self.set_disabled(PrimitiveKind::Str)
+    }
+
+    /// Never garble integers
+    pub fn disable_integers(self) -> Self {
+        INTEGERS.into_iter().fold(self, Self::set_disabled)
+    }
+
+    /// Never garble floating point numbers
+    pub fn disable_floats(self) -> Self {
+        FLOATS.into_iter().fold(self, Self::set_disabled)
+    }
+
+    fn set_rate(mut self, kind: PrimitiveKind, rate: f64) -> Self {
+        self.rates[kind as usize] = Some(rate);
+        self.disabled[kind as usize] = false;
+        self
+    }
+
+    fn set_disabled(mut self, kind: PrimitiveKind) -> Self {
+        self.rates[kind as usize] = Some(0.0);
+        self.disabled[kind as usize] = true;
+        self
+    }
+
+    /// Set the rate for structural faults
+    ///
+    /// Structural faults change the shape of a value rather than a single
+    /// primitive, such as inverting the bounds of a range or changing the
+    /// length of a string. Defaults to 0.0, so structural faults are only
+    /// applied when enabled explicitly.
+    pub fn structural_rate(mut self, rate: f64) -> Self {
+        self.structural_rate = rate;
+        self
+    }
+
+    /// Set the length of padded strings
+    ///
+    /// When padding a string, characters are added until the string reaches
+    /// this length. Defaults to 64 KiB.
+    pub fn pad_str_len(mut self, len: usize) -> Self {
+        self.pad_str_len = len;
+        self
+    }
+
+    /// Set the maximum length of garbled strings
+    ///
+    /// Length faults never make a string longer than this, which includes
+    /// padding. Defaults to 1 MiB.
+    pub fn max_str_len(mut self, len: usize) -> Self {
+        self.max_str_len = len;
+        self
+    }
+
+    /// Set the depth of deeply nested JSON values
+    ///
+    /// When nesting a JSON value, it is wrapped in this many arrays. A depth
+    /// of 256 is above the recursion limit of most JSON parsers.
+    ///
+    /// Every node of a document can be nested, so the depth of a garbled
+    /// document can grow with its number of nodes. Defaults to 0, which
+    /// disables deep nesting.
+    #[cfg(feature = "serde_json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde_json")))]
+    pub fn json_nesting_depth(mut self, depth: usize) -> Self {
+        self.json_nesting_depth = depth;
+        self
+    }
+
+    /// Create the [`SimpleGarbler`]
+    ///
+    /// Returns an [`Error::InvalidRate`] if any rate is not between 0.0 and
+    /// 1.0.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn build(self) -> Result<SimpleGarbler, Error> {
+        self.build_with_rng(rand::thread_rng())
+    }
+
+    /// Create the [`SimpleGarbler`] with the given random number generator
+    ///
+    /// Returns an [`Error::InvalidRate`] if any rate is not between 0.0 and
+    /// 1.0.
+    pub fn build_with_rng<R>(self, rng: R) -> Result<SimpleGarbler<R>, Error>
+    where
+        R: Rng,
+    {
+        let mut rates = [0.0; PrimitiveKind::COUNT];
+        for (rate, custom) in rates.iter_mut().zip(self.rates) {
+            *rate = check_rate(custom.unwrap_or(self.rate))?;
+        }
+
+        Ok(SimpleGarbler {
+            rates,
+            disabled: self.disabled,
+            structural_rate: check_rate(self.structural_rate)?,
+            pad_str_len: self.pad_str_len,
+            max_str_len: self.max_str_len,
+            #[cfg(feature = "serde_json")]
+            json_nesting_depth: self.json_nesting_depth,
+            rng,
+        })
     }
 }
 
@@ -30,7 +381,7 @@ macro_rules! impl_func {
     ($t:ty) => {
         paste! {
             fn [<garble_ $t:lower>](&mut self, value: $t) -> $t {
-                if self.should_garble() {
+                if self.should_garble(PrimitiveKind::[<$t:camel>]) {
                     self.rng.gen()
                 } else {
                     value
@@ -39,7 +390,10 @@ macro_rules! impl_func {
         }
     };
 }
-impl Garbler for SimpleGarbler {
+impl<R> Garbler for SimpleGarbler<R>
+where
+    R: Rng,
+{
     impl_func! { char }
     impl_func! { u8 }
     impl_func! { u16 }
@@ -57,28 +411,344 @@ impl Garbler for SimpleGarbler {
     impl_func! { f64 }
 
     fn garble_bool(&mut self, value: bool) -> bool {
-        self.should_garble() != value
+        self.should_garble(PrimitiveKind::Bool) != value
     }
 
     fn garble_str<T>(&mut self, value: T) -> String
     where
         T: AsRef<str>,
     {
-        value
+        let value = self.garble_chars(value.as_ref(), PrimitiveKind::Str);
+        self.garble_len(value.chars().collect(), PrimitiveKind::Str, |rng| rng.gen())
+            .into_iter()
+            .collect()
+    }
+
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        // C strings cannot contain nul bytes
+        let bytes = value
             .as_ref()
-            .chars()
-            .map(|c| {
-                if self.should_garble() {
+            .to_bytes()
+            .iter()
+            .map(|b| {
+                if self.should_garble(PrimitiveKind::CStr) {
+                    self.rng.gen_range(1..=u8::MAX)
+                } else {
+                    *b
+                }
+            })
+            .collect();
+        let bytes = self.garble_len(bytes, PrimitiveKind::CStr, |rng| rng.gen_range(1..=u8::MAX));
+        CString::new(bytes).unwrap()
+    }
+
+    #[cfg(all(feature = "std", unix))]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+        let bytes = value
+            .as_ref()
+            .as_bytes()
+            .iter()
+            .map(|b| {
+                if self.should_garble(PrimitiveKind::OsStr) {
                     self.rng.gen()
                 } else {
-                    c
+                    *b
                 }
             })
-            .collect()
+            .collect();
+        OsString::from_vec(bytes)
+    }
+
+    #[cfg(all(feature = "std", not(unix)))]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        let value = value.as_ref();
+        match value.to_str() {
+            Some(s) => self.garble_chars(s, PrimitiveKind::OsStr).into(),
+            None => value.to_os_string(),
+        }
     }
+
+    #[cfg(feature = "std")]
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        let value = value.as_ref();
+        if !self.should_garble(PrimitiveKind::Path) {
+            return value.to_path_buf();
+        }
+
+        match self.rng.gen_range(0..5) {
+            // Traversal to a parent directory
+            0 => {
+                let components = value.iter().collect::<Vec<_>>();
+                let min = usize::from(value.has_root());
+                let at = self.rng.gen_range(min..=components.len().max(min));
+                let mut garbled = PathBuf::new();
+                for (index, component) in components.iter().enumerate() {
+                    if index == at {
+                        garbled.push("..");
+                    }
+                    garbled.push(component);
+                }
+                if at >= components.len() {
+                    garbled.push("..");
+                }
+                garbled
+            }
+            // Trailing separator
+            1 => {
+                let mut garbled = value.as_os_str().to_os_string();
+                garbled.push(path::MAIN_SEPARATOR_STR);
+                garbled.into()
+            }
+            // Component longer than most file systems allow
+            2 => {
+                let len = self.rng.gen_range(NAME_MAX + 1..=16 * NAME_MAX);
+                value.join("x".repeat(len))
+            }
+            // Embedded nul byte, which the standard library rejects when
+            // passing the path to the operating system
+            3 => {
+                let mut garbled = value.as_os_str().to_os_string();
+                garbled.push("\0");
+                garbled.into()
+            }
+            // Invalid Unicode
+            _ => {
+                let mut garbled = value.as_os_str().to_os_string();
+                garbled.push(invalid_unicode());
+                garbled.into()
+            }
+        }
+    }
+
+    fn structural_fault(&mut self, faults: usize) -> Option<usize> {
+        if faults == 0 || !self.should_garble_structure() {
+            return None;
+        }
+        Some(self.rng.gen_range(0..faults))
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn garble_json_structure(&mut self, value: serde_json::Value) -> serde_json::Value {
+        use alloc::{string::ToString, vec};
+        use serde_json::Value;
+
+        if !self.should_garble_structure() {
+            return value;
+        }
+
+        let nest = |value| (0..self.json_nesting_depth).fold(value, |v, _| Value::Array(vec![v]));
+        let faults = if self.json_nesting_depth > 0 { 3 } else { 2 };
+        match (self.rng.gen_range(0..faults), value) {
+            // Null injection
+            (0, Value::Null) => nest(Value::Null),
+            (0, _) => Value::Null,
+            // Deep nesting
+            (2, value) => nest(value),
+            // Type confusion
+            (_, Value::Number(v)) => Value::String(v.to_string()),
+            (_, Value::Bool(v)) => Value::String(v.to_string()),
+            // Array truncation
+            (_, Value::Array(mut v)) if !v.is_empty() => {
+                v.truncate(self.rng.gen_range(0..v.len()));
+                Value::Array(v)
+            }
+            // Key removal or renaming
+            (_, Value::Object(mut v)) if !v.is_empty() => {
+                let index = self.rng.gen_range(0..v.len());
+                let mut key = v.keys().nth(index).cloned().unwrap();
+                let value = v.remove(&key).unwrap();
+                if self.rng.gen() {
+                    key.push(self.rng.gen());
+                    v.insert(key, value);
+                }
+                Value::Object(v)
+            }
+            (_, Value::Null) => nest(Value::Null),
+            (_, _) => Value::Null,
+        }
+    }
+
+    fn garble_ipv4_addr(&mut self, value: Ipv4Addr) -> Ipv4Addr {
+        if !self.should_garble(PrimitiveKind::Ipv4Addr) {
+            return value;
+        }
+
+        match self.rng.gen_range(0..7) {
+            // Loopback
+            0 => Ipv4Addr::new(127, 0, 0, self.rng.gen_range(1..=u8::MAX)),
+            // Unspecified
+            1 => Ipv4Addr::UNSPECIFIED,
+            // Broadcast
+            2 => Ipv4Addr::BROADCAST,
+            // Multicast, 224.0.0.0/4
+            3 => Ipv4Addr::from(0xE000_0000 | (self.rng.gen::<u32>() >> 4)),
+            // Link-local, 169.254.0.0/16
+            4 => Ipv4Addr::new(169, 254, self.rng.gen(), self.rng.gen()),
+            // Private ranges
+            5 => match self.rng.gen_range(0..3) {
+                0 => Ipv4Addr::new(10, self.rng.gen(), self.rng.gen(), self.rng.gen()),
+                1 => Ipv4Addr::new(
+                    172,
+                    self.rng.gen_range(16..32),
+                    self.rng.gen(),
+                    self.rng.gen(),
+                ),
+                _ => Ipv4Addr::new(192, 168, self.rng.gen(), self.rng.gen()),
+            },
+            // Random address
+            _ => Ipv4Addr::from(self.rng.gen::<u32>()),
+        }
+    }
+
+    fn garble_ipv6_addr(&mut self, value: Ipv6Addr) -> Ipv6Addr {
+        if !self.should_garble(PrimitiveKind::Ipv6Addr) {
+            return value;
+        }
+
+        match self.rng.gen_range(0..6) {
+            // Loopback
+            0 => Ipv6Addr::LOCALHOST,
+            // Unspecified
+            1 => Ipv6Addr::UNSPECIFIED,
+            // Multicast, ff00::/8
+            2 => Ipv6Addr::from(0xFF << 120 | (self.rng.gen::<u128>() >> 8)),
+            // Link-local, fe80::/10
+            3 => Ipv6Addr::from(0xFE80 << 112 | (self.rng.gen::<u128>() >> 64)),
+            // Unique local, fc00::/7
+            4 => Ipv6Addr::from(0xFC << 120 | (self.rng.gen::<u128>() >> 7)),
+            // IPv4-mapped address
+            _ => Ipv4Addr::from(self.rng.gen::<u32>()).to_ipv6_mapped(),
+        }
+    }
+
+    fn garble_socket_addr_v4(&mut self, value: SocketAddrV4) -> SocketAddrV4 {
+        let ip = self.garble_ipv4_addr(*value.ip());
+        let port = if self.should_garble(PrimitiveKind::SocketAddrV4) {
+            self.port()
+        } else {
+            value.port()
+        };
+        SocketAddrV4::new(ip, port)
+    }
+
+    fn garble_socket_addr_v6(&mut self, value: SocketAddrV6) -> SocketAddrV6 {
+        let ip = self.garble_ipv6_addr(*value.ip());
+        if !self.should_garble(PrimitiveKind::SocketAddrV6) {
+            return SocketAddrV6::new(ip, value.port(), value.flowinfo(), value.scope_id());
+        }
+
+        let port = self.port();
+        let scope_id = match self.rng.gen_range(0..3) {
+            0 => 0,
+            1 => u32::MAX,
+            _ => self.rng.gen(),
+        };
+        SocketAddrV6::new(ip, port, self.rng.gen(), scope_id)
+    }
+
+    fn garble_duration(&mut self, value: Duration) -> Duration {
+        if !self.should_garble(PrimitiveKind::Duration) {
+            return value;
+        }
+
+        match self.rng.gen_range(0..5) {
+            // Zero duration
+            0 => Duration::ZERO,
+            // Largest possible duration
+            1 => Duration::MAX,
+            // Huge duration, between 1 and 100 years
+            2 => Duration::from_secs(self.rng.gen_range(YEAR..=100 * YEAR)),
+            // Off by one nanosecond
+            3 => value
+                .checked_sub(Duration::from_nanos(1))
+                .unwrap_or(Duration::from_nanos(1)),
+            // Random duration
+            _ => Duration::new(self.rng.gen(), self.rng.gen_range(0..NANOS_PER_SEC)),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_system_time(&mut self, value: SystemTime) -> SystemTime {
+        if !self.should_garble(PrimitiveKind::SystemTime) {
+            return value;
+        }
+
+        let garbled = match self.rng.gen_range(0..6) {
+            // Unix epoch
+            0 => Some(time::UNIX_EPOCH),
+            // Before the Unix epoch, up to 100 years
+            1 => time::UNIX_EPOCH
+                .checked_sub(Duration::from_secs(self.rng.gen_range(1..=100 * YEAR))),
+            // Clock skew forward, up to a year
+            2 => value.checked_add(self.skew(Duration::from_secs(YEAR))),
+            // Clock skew backward, up to a year
+            3 => value.checked_sub(self.skew(Duration::from_secs(YEAR))),
+            // Overflow of 32-bit signed Unix timestamps
+            4 => time::UNIX_EPOCH.checked_add(Duration::from_secs(1 << 31)),
+            // Far future, between 100 and 10000 years
+            _ => time::UNIX_EPOCH.checked_add(Duration::from_secs(
+                self.rng.gen_range(100 * YEAR..=10_000 * YEAR),
+            )),
+        };
+        garbled.unwrap_or(value)
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_instant(&mut self, value: Instant) -> Instant {
+        if !self.should_garble(PrimitiveKind::Instant) {
+            return value;
+        }
+
+        // Instants are opaque and platform-specific, so we can only move them
+        // by an offset and fall back to the other direction on overflow.
+        let skew = self.skew(Duration::from_secs(24 * 60 * 60));
+        if self.rng.gen() {
+            value.checked_add(skew).or_else(|| value.checked_sub(skew))
+        } else {
+            value.checked_sub(skew).or_else(|| value.checked_add(skew))
+        }
+        .unwrap_or(value)
+    }
+}
+
+/// OS string that is not valid Unicode on the current platform
+#[cfg(all(feature = "std", unix))]
+fn invalid_unicode() -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+
+    OsString::from_vec(vec![0xFF, 0xFE])
 }
 
-#[cfg(test)]
+/// OS string that is not valid Unicode on the current platform
+#[cfg(all(feature = "std", windows))]
+fn invalid_unicode() -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+
+    // Unpaired surrogate
+    OsString::from_wide(&[0xD800])
+}
+
+/// OS string that is not valid Unicode on the current platform
+#[cfg(all(feature = "std", not(any(unix, windows))))]
+fn invalid_unicode() -> OsString {
+    OsString::from("\u{FFFD}")
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     use crate::Garble;
@@ -94,7 +764,7 @@ mod tests {
                     #[test]
                     fn [<test_0pc>]() {
                         // GIVEN a SimpleGarbler with a rate of 0%
-                        let mut garbler = SimpleGarbler::new(0.0);
+                        let mut garbler = SimpleGarbler::new(0.0).unwrap();
                         // WHEN we garble a value
                         let value = $v.garble(&mut garbler);
                         // THEN the value should be the same as the original
@@ -104,7 +774,7 @@ mod tests {
                     #[test]
                     fn [<test_100pc>]() {
                         // GIVEN a SimpleGarbler with a rate of 100%
-                        let mut garbler = SimpleGarbler::new(1.0);
+                        let mut garbler = SimpleGarbler::new(1.0).unwrap();
                         // WHEN we garble a value
                         let value = $v.garble(&mut garbler);
                         // THEN the value should be different
@@ -114,7 +784,7 @@ mod tests {
                     #[test]
                     fn [<test_100pc_option>]() {
                         // GIVEN a SimpleGarbler with a rate of 100%
-                        let mut garbler = SimpleGarbler::new(1.0);
+                        let mut garbler = SimpleGarbler::new(1.0).unwrap();
                         // WHEN we garble an option
                         let value = Some($v).garble(&mut garbler);
                         // THEN the value should be different
@@ -128,7 +798,7 @@ mod tests {
                     #[test]
                     fn [<test_100pc_result>]() {
                         // GIVEN a SimpleGarbler with a rate of 100%
-                        let mut garbler = SimpleGarbler::new(1.0);
+                        let mut garbler = SimpleGarbler::new(1.0).unwrap();
                         // WHEN we garble a result
                         let value = Ok::<_, ()>($v).garble(&mut garbler);
                         // THEN the value should be different
@@ -142,7 +812,7 @@ mod tests {
                     #[test]
                     fn [<test_100pc_vec>]() {
                         // GIVEN a SimpleGarbler with a rate of 100%
-                        let mut garbler = SimpleGarbler::new(1.0);
+                        let mut garbler = SimpleGarbler::new(1.0).unwrap();
                         // WHEN we garble a vector
                         let value = vec![$v].garble(&mut garbler);
                         // THEN the value should be different
@@ -152,11 +822,11 @@ mod tests {
                     #[test]
                     fn [<test_100pc_boxed>]() {
                         // GIVEN a SimpleGarbler with a rate of 100%
-                        let mut garbler = SimpleGarbler::new(1.0);
+                        let mut garbler = SimpleGarbler::new(1.0).unwrap();
                         // WHEN we garble a boxed value
                         let value = Box::new($v).garble(&mut garbler);
                         // THEN the value should be different
-                        assert_ne!(value, $v);
+                        assert_ne!(*value, $v);
                     }
                 }
             }
@@ -216,4 +886,372 @@ mod tests {
     // String types
     test_case! { String => (short, String::from("hello, world")) }
     test_case! { str => (shprt, "hello, world") }
+
+    // Network types
+    test_case! { Ipv4Addr => (public, Ipv4Addr::new(93, 184, 216, 34)) }
+    test_case! { Ipv6Addr => (public, Ipv6Addr::new(0x2606, 0x2800, 0x220, 1, 0x248, 0x1893, 0x25c8, 0x1946)) }
+
+    #[test]
+    fn test_socket_addr_v6() {
+        // GIVEN a SimpleGarbler that only garbles socket addresses
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .rate_for::<SocketAddrV6>(1.0)
+            .build()
+            .unwrap();
+        for _ in 0..32 {
+            // WHEN we garble a socket address
+            let addr = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 8080, 0, 3);
+            let value = addr.garble(&mut garbler);
+            // THEN the IP should be the same
+            assert_eq!(value.ip(), addr.ip());
+            // AND the port or scope ID should be different
+            assert!(value.port() != addr.port() || value.scope_id() != addr.scope_id());
+        }
+    }
+
+    // Time types
+    test_case! { Duration => (secs, Duration::from_secs(30)) }
+    test_case! { Duration => (nanos, Duration::from_nanos(1_500)) }
+    test_case! { SystemTime => (epoch, time::UNIX_EPOCH + Duration::from_secs(1_000_000)) }
+
+    #[test]
+    fn test_str_len() {
+        // GIVEN a SimpleGarbler that only changes the length of strings
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .max_str_len(128)
+            .build()
+            .unwrap();
+        for _ in 0..32 {
+            // WHEN we garble a string
+            let value = "hello, world".garble(&mut garbler);
+            // THEN the length should be different
+            assert_ne!(value.chars().count(), 12);
+            // AND the length should be capped
+            assert!(value.chars().count() <= 128);
+        }
+    }
+
+    #[test]
+    fn test_str_len_default() {
+        // GIVEN a SimpleGarbler with the default structural rate
+        let mut garbler = SimpleGarbler::new(1.0).unwrap();
+        for _ in 0..32 {
+            // WHEN we garble a string
+            let value = "hello, world".garble(&mut garbler);
+            // THEN the length should be the same
+            assert_eq!(value.chars().count(), 12);
+        }
+    }
+
+    #[test]
+    fn test_str_len_disabled() {
+        // GIVEN a SimpleGarbler that applies structural faults, but does not
+        // garble strings
+        let mut garbler = SimpleGarbler::builder()
+            .disable_str()
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble a string
+        let value = "hello, world".garble(&mut garbler);
+        // THEN the value should be the same as the original
+        assert_eq!(value, "hello, world");
+    }
+
+    #[test]
+    fn test_str_pad() {
+        // GIVEN a SimpleGarbler that pads C strings
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .pad_str_len(1_000)
+            .build()
+            .unwrap();
+        for _ in 0..32 {
+            // WHEN we garble a C string
+            let value = CString::new("hello").unwrap().garble(&mut garbler);
+            // THEN the length should be different and at most the padding length
+            assert_ne!(value.as_bytes().len(), 5);
+            assert!(value.as_bytes().len() <= 1_000);
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        // GIVEN a SimpleGarbler that only garbles structures
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble a range
+        let value = (1..10).garble(&mut garbler);
+        // THEN the range should be inverted or empty
+        assert!(value.is_empty());
+        assert!((value.start, value.end) == (10, 1) || (value.start, value.end) == (1, 1));
+    }
+
+    #[test]
+    fn test_range_inclusive() {
+        // GIVEN a SimpleGarbler that only garbles structures
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        for _ in 0..32 {
+            // WHEN we garble an inclusive range
+            let value = (1..=10).garble(&mut garbler);
+            // THEN the range should be inverted, which also makes it empty
+            assert!(value.is_empty(), "{value:?}");
+            assert_eq!((*value.start(), *value.end()), (10, 1));
+        }
+    }
+
+    #[test]
+    fn test_range_wrappers() {
+        use crate::{ChainGarbler, RecordingGarbler};
+
+        // GIVEN a SimpleGarbler that only garbles structures
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble ranges through references and wrappers
+        let values = [
+            (1..10).garble(&mut &mut garbler),
+            (1..10).garble(&mut RecordingGarbler::new(&mut garbler)),
+            (1..10).garble(&mut ChainGarbler::new().with(&mut garbler)),
+        ];
+        // THEN the ranges should be inverted or empty
+        for value in values {
+            assert!(value.is_empty(), "{value:?}");
+        }
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_json_structure() {
+        // GIVEN a SimpleGarbler that only garbles structures
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .json_nesting_depth(8)
+            .build()
+            .unwrap();
+        for _ in 0..32 {
+            // WHEN we garble a JSON value
+            let value = serde_json::json!({"a": [1, true, "b", null], "c": {"d": 2.5}});
+            let garbled = value.clone().garble(&mut garbler);
+            // THEN the value should be different
+            assert_ne!(garbled, value);
+        }
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_json_structure_recording() {
+        use crate::RecordingGarbler;
+
+        // GIVEN a SimpleGarbler that only garbles JSON structures, wrapped in
+        // a RecordingGarbler
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .disable_str()
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        let mut recording = RecordingGarbler::new(&mut garbler);
+        for _ in 0..32 {
+            // WHEN we garble a JSON value
+            let value = serde_json::json!({"a": [1, true, "b", null], "c": {"d": 2.5}});
+            let garbled = value.clone().garble(&mut recording);
+            // THEN the structural faults should be applied
+            assert_ne!(garbled, value);
+        }
+        // AND they should not be recorded
+        assert!(recording.log().is_empty());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_json_nesting_default() {
+        fn depth(value: &serde_json::Value) -> usize {
+            match value {
+                serde_json::Value::Array(v) => 1 + v.iter().map(depth).max().unwrap_or(0),
+                serde_json::Value::Object(v) => 1 + v.values().map(depth).max().unwrap_or(0),
+                _ => 0,
+            }
+        }
+
+        // GIVEN a SimpleGarbler that only garbles structures, with the
+        // default nesting depth
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        for _ in 0..32 {
+            // WHEN we garble a JSON value
+            let value = serde_json::json!([[1, 2], {"a": [null]}]);
+            let garbled = value.clone().garble(&mut garbler);
+            // THEN the value should not be nested any deeper
+            assert!(depth(&garbled) <= depth(&value), "{garbled}");
+        }
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_json_leaves() {
+        // GIVEN a SimpleGarbler that does not garble structures
+        let mut garbler = SimpleGarbler::builder()
+            .rate(1.0)
+            .structural_rate(0.0)
+            .build()
+            .unwrap();
+        // WHEN we garble a JSON value
+        let value = serde_json::json!({"a": true, "b": [false]});
+        let garbled = value.garble(&mut garbler);
+        // THEN only the leaves should be different
+        assert_eq!(garbled, serde_json::json!({"a": false, "b": [true]}));
+    }
+
+    #[test]
+    fn test_path() {
+        // GIVEN a SimpleGarbler with a rate of 100%
+        let mut garbler = SimpleGarbler::new(1.0).unwrap();
+        for _ in 0..32 {
+            // WHEN we garble a path
+            let path = PathBuf::from("/var/lib/data.bin");
+            let value = path.clone().garble(&mut garbler);
+            // THEN the value should be different
+            assert_ne!(value.as_os_str(), path.as_os_str());
+        }
+    }
+
+    #[test]
+    fn test_os_str_disabled() {
+        // GIVEN a SimpleGarbler that does not garble OS strings
+        let mut garbler = SimpleGarbler::builder()
+            .rate(1.0)
+            .disable::<OsStr>()
+            .build()
+            .unwrap();
+        // WHEN we garble an OS string
+        let value = OsString::from("hello").garble(&mut garbler);
+        // THEN the value should be the same as the original
+        assert_eq!(value, OsString::from("hello"));
+    }
+
+    #[test]
+    fn test_instant() {
+        // GIVEN a SimpleGarbler with a rate of 100%
+        let mut garbler = SimpleGarbler::new(1.0).unwrap();
+        // WHEN we garble an instant
+        let instant = Instant::now();
+        let value = instant.garble(&mut garbler);
+        // THEN the value should be different
+        assert_ne!(value, instant);
+    }
+
+    #[test]
+    fn test_builder_rate_for() {
+        // GIVEN a SimpleGarbler that only garbles booleans
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .rate_for::<bool>(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble values
+        let value = (true, 1u32, "hello").garble(&mut garbler);
+        // THEN only the boolean should be different
+        assert_eq!(value, (false, 1u32, "hello".to_string()));
+    }
+
+    #[test]
+    fn test_builder_disable() {
+        // GIVEN a SimpleGarbler that garbles everything but strings and u32
+        let mut garbler = SimpleGarbler::builder()
+            .rate(1.0)
+            .disable::<u32>()
+            .disable_str()
+            .build()
+            .unwrap();
+        // WHEN we garble values
+        let value = (true, 1u32, "hello").garble(&mut garbler);
+        // THEN only the boolean should be different
+        assert_eq!(value, (false, 1u32, "hello".to_string()));
+    }
+
+    #[test]
+    fn test_builder_rate_for_integers() {
+        // GIVEN a SimpleGarbler that only garbles integers
+        let mut garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .rate_for_integers(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble values
+        let value = (true, 1u64, -1i64, 1.5f64).garble(&mut garbler);
+        // THEN only the integers should be different
+        assert!(value.0);
+        assert_ne!(value.1, 1);
+        assert_ne!(value.2, -1);
+        assert_eq!(value.3, 1.5);
+    }
+
+    #[test]
+    fn test_builder_disable_families() {
+        // GIVEN a SimpleGarbler that garbles everything but integers and
+        // floating point numbers
+        let mut garbler = SimpleGarbler::builder()
+            .rate(1.0)
+            .disable_integers()
+            .disable_floats()
+            .build()
+            .unwrap();
+        // WHEN we garble values
+        let value = (true, 1u8, -1isize, 1.5f32, 2.5f64).garble(&mut garbler);
+        // THEN only the boolean should be different
+        assert_eq!(value, (false, 1u8, -1isize, 1.5f32, 2.5f64));
+    }
+
+    #[test]
+    fn test_with_rng() {
+        // GIVEN two SimpleGarblers with the same seeded random number generator
+        let mut a = SimpleGarbler::with_rng(0.5, StdRng::seed_from_u64(42)).unwrap();
+        let mut b = SimpleGarbler::builder()
+            .build_with_rng(StdRng::seed_from_u64(42))
+            .unwrap();
+        // WHEN we garble the same value
+        let value = (1u32, "hello", [true; 8], 'c');
+        // THEN both values should be garbled identically
+        assert_eq!(value.garble(&mut a), value.garble(&mut b));
+    }
+
+    #[test]
+    fn test_invalid_rate() {
+        assert_eq!(
+            SimpleGarbler::new(1.5).unwrap_err(),
+            Error::InvalidRate(1.5)
+        );
+        assert_eq!(
+            SimpleGarbler::new(-0.5).unwrap_err(),
+            Error::InvalidRate(-0.5)
+        );
+        assert!(matches!(
+            SimpleGarbler::new(f64::NAN),
+            Err(Error::InvalidRate(rate)) if rate.is_nan()
+        ));
+    }
+
+    #[test]
+    fn test_builder_invalid_rate() {
+        let garbler = SimpleGarbler::builder().rate_for::<u8>(2.0).build();
+        assert_eq!(garbler.unwrap_err(), Error::InvalidRate(2.0));
+    }
 }
@@ -0,0 +1,231 @@
+use super::CountingGarbler;
+use crate::{Garble, Garbler};
+use alloc::{
+    borrow::ToOwned,
+    ffi::CString,
+    string::{String, ToString},
+};
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+use paste::paste;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// [`Garbler`] that only garbles a single leaf of a value
+///
+/// Leaves are counted in the order the primitive `garble_*` functions are
+/// called, starting at zero. The leaf at the target index is passed to the
+/// inner garbler, while all other leaves are left untouched. Structural
+/// faults, such as inverted ranges, are never applied.
+#[derive(Debug)]
+pub struct TargetedGarbler<G> {
+    inner: G,
+    target: usize,
+    index: usize,
+}
+
+impl<G> TargetedGarbler<G>
+where
+    G: Garbler,
+{
+    /// Create a new [`TargetedGarbler`] that garbles the leaf at `target`
+    /// with the inner garbler
+    pub fn new(inner: G, target: usize) -> Self {
+        Self {
+            inner,
+            target,
+            index: 0,
+        }
+    }
+
+    /// Number of leaves seen so far
+    pub fn count(&self) -> usize {
+        self.index
+    }
+
+    /// Consume this garbler and return the inner garbler
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+
+    fn should_garble(&mut self) -> bool {
+        let hit = self.index == self.target;
+        self.index += 1;
+        hit
+    }
+}
+
+macro_rules! impl_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t {
+                    if self.should_garble() {
+                        self.inner.[<garble_ $t:snake>](value)
+                    } else {
+                        value
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl<G> Garbler for TargetedGarbler<G>
+where
+    G: Garbler,
+{
+    impl_func! { bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6 }
+
+    fn garble_str<T>(&mut self, value: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        if self.should_garble() {
+            self.inner.garble_str(value)
+        } else {
+            value.as_ref().to_string()
+        }
+    }
+
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        if self.should_garble() {
+            self.inner.garble_c_str(value)
+        } else {
+            value.as_ref().to_owned()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        if self.should_garble() {
+            self.inner.garble_os_str(value)
+        } else {
+            value.as_ref().to_os_string()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        if self.should_garble() {
+            self.inner.garble_path(value)
+        } else {
+            value.as_ref().to_path_buf()
+        }
+    }
+}
+
+/// Iterator over all single-leaf faults of a value
+///
+/// This yields one garbled copy of the value per leaf, where only that leaf
+/// was passed to the inner garbler. See [`Garbler::single_faults`].
+#[derive(Debug)]
+pub struct SingleFaults<T, G> {
+    value: T,
+    garbler: G,
+    index: usize,
+    len: usize,
+}
+
+impl<T, G> SingleFaults<T, G>
+where
+    T: Garble + Clone,
+    G: Garbler,
+{
+    /// Create a new [`SingleFaults`] iterator
+    ///
+    /// This performs a dry-run with a [`CountingGarbler`] to find the number
+    /// of leaves in the value.
+    pub fn new(value: T, garbler: G) -> Self {
+        let len = CountingGarbler::count_leaves(&value);
+        Self {
+            value,
+            garbler,
+            index: 0,
+            len,
+        }
+    }
+}
+
+impl<T, G> Iterator for SingleFaults<T, G>
+where
+    T: Garble + Clone,
+    G: Garbler,
+{
+    type Item = T::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len {
+            return None;
+        }
+
+        let mut garbler = TargetedGarbler::new(&mut self.garbler, self.index);
+        self.index += 1;
+        Some(self.value.clone().garble(&mut garbler))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T, G> ExactSizeIterator for SingleFaults<T, G>
+where
+    T: Garble + Clone,
+    G: Garbler,
+{
+}
+
+#[cfg(all(test, feature = "std", feature = "simple"))]
+mod tests {
+    use super::*;
+    use crate::SimpleGarbler;
+
+    #[test]
+    fn test_targeted() {
+        // GIVEN a TargetedGarbler for the second leaf
+        let mut garbler = TargetedGarbler::new(SimpleGarbler::new(1.0).unwrap(), 1);
+        // WHEN we garble a value
+        let value = (true, true, true).garble(&mut garbler);
+        // THEN only the second leaf should be different
+        assert_eq!(value, (true, false, true));
+    }
+
+    #[test]
+    fn test_single_faults() {
+        // GIVEN a value with 3 leaves
+        let value = [true, true, true];
+        // WHEN we enumerate single faults
+        let faults = SimpleGarbler::new(1.0)
+            .unwrap()
+            .single_faults(value)
+            .collect::<Vec<_>>();
+        // THEN each copy should have exactly one garbled leaf
+        assert_eq!(
+            faults,
+            vec![
+                [false, true, true],
+                [true, false, true],
+                [true, true, false]
+            ]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use super::DynGarbler;
+use crate::{Error, Garbler};
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    ffi::CString,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+use paste::paste;
+use rand::prelude::*;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// [`Garbler`] that picks an inner garbler for each primitive value
+///
+/// Each time a primitive value is garbled, one of the inner garblers is
+/// picked randomly according to its weight.
+///
+/// With the `std` feature, this uses the thread-local random number generator
+/// by default. Without it, a random number generator must be provided with
+/// [`with_rng`](Self::with_rng).
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub struct WeightedGarbler<
+    'a,
+    #[cfg(feature = "std")] R = ThreadRng,
+    #[cfg(not(feature = "std"))] R,
+> {
+    garblers: Vec<(f64, Box<dyn DynGarbler + 'a>)>,
+    total: f64,
+    rng: R,
+}
+
+#[cfg(feature = "std")]
+impl WeightedGarbler<'_> {
+    /// Create a new, empty [`WeightedGarbler`]
+    ///
+    /// Without any inner garbler, values are left untouched.
+    pub fn new() -> Self {
+        Self::with_rng(rand::thread_rng())
+    }
+}
+
+impl<'a, R> WeightedGarbler<'a, R>
+where
+    R: Rng,
+{
+    /// Create a new, empty [`WeightedGarbler`] with the given random number
+    /// generator
+    pub fn with_rng(rng: R) -> Self {
+        Self {
+            garblers: Vec::new(),
+            total: 0.0,
+            rng,
+        }
+    }
+
+    /// Add a garbler with the given weight
+    ///
+    /// Weights are relative to each other and do not need to add up to 1.
+    ///
+    /// Returns an [`Error::InvalidWeight`] if the weight is negative or not
+    /// finite, or if the total of all weights is not finite.
+    pub fn with<G>(mut self, weight: f64, garbler: G) -> Result<Self, Error>
+    where
+        G: Garbler + 'a,
+    {
+        let total = self.total + weight;
+        if !weight.is_finite() || weight < 0.0 || !total.is_finite() {
+            return Err(Error::InvalidWeight(weight));
+        }
+        self.total = total;
+        self.garblers.push((weight, Box::new(garbler)));
+        Ok(self)
+    }
+
+    fn pick(&mut self) -> Option<&mut (dyn DynGarbler + 'a)> {
+        if self.total <= 0.0 {
+            return None;
+        }
+
+        let mut target = self.rng.gen_range(0.0..self.total);
+        let last = self
+            .garblers
+            .iter()
+            .rposition(|(weight, _)| *weight > 0.0)?;
+        for (index, (weight, garbler)) in self.garblers.iter_mut().enumerate() {
+            if target < *weight || index == last {
+                return Some(garbler.as_mut());
+            }
+            target -= *weight;
+        }
+        None
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for WeightedGarbler<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> fmt::Debug for WeightedGarbler<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeightedGarbler")
+            .field(
+                "weights",
+                &self.garblers.iter().map(|(w, _)| *w).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+macro_rules! impl_func {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        $(
+            paste! {
+                $(#[$meta])*
+                fn [<garble_ $t:snake>](&mut self, value: $t) -> $t {
+                    match self.pick() {
+                        Some(garbler) => garbler.[<garble_ $t:snake>](value),
+                        None => value,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl<R> Garbler for WeightedGarbler<'_, R>
+where
+    R: Rng,
+{
+    impl_func! { bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, Duration, #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6 }
+
+    fn garble_str<T>(&mut self, value: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        match self.pick() {
+            Some(garbler) => garbler.garble_str(value.as_ref()),
+            None => value.as_ref().to_string(),
+        }
+    }
+
+    fn garble_c_str<T>(&mut self, value: T) -> CString
+    where
+        T: AsRef<CStr>,
+    {
+        match self.pick() {
+            Some(garbler) => garbler.garble_c_str(value.as_ref()),
+            None => value.as_ref().to_owned(),
+        }
+    }
+
+    fn structural_fault(&mut self, faults: usize) -> Option<usize> {
+        self.pick()?.structural_fault(faults)
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn garble_json_structure(&mut self, value: serde_json::Value) -> serde_json::Value {
+        match self.pick() {
+            Some(garbler) => garbler.garble_json_structure(value),
+            None => value,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_os_str<T>(&mut self, value: T) -> OsString
+    where
+        T: AsRef<OsStr>,
+    {
+        match self.pick() {
+            Some(garbler) => garbler.garble_os_str(value.as_ref()),
+            None => value.as_ref().to_os_string(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn garble_path<T>(&mut self, value: T) -> PathBuf
+    where
+        T: AsRef<Path>,
+    {
+        match self.pick() {
+            Some(garbler) => garbler.garble_path(value.as_ref()),
+            None => value.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{Garble, SimpleGarbler};
+
+    #[test]
+    fn test_empty() {
+        // GIVEN an empty WeightedGarbler
+        let mut garbler = WeightedGarbler::new();
+        // WHEN we garble a value
+        let value = (true, 1u32).garble(&mut garbler);
+        // THEN the value should be the same as the original
+        assert_eq!(value, (true, 1u32));
+    }
+
+    #[test]
+    fn test_zero_weight() {
+        // GIVEN a WeightedGarbler where only the passthrough garbler has a weight
+        let mut garbler = WeightedGarbler::new()
+            .with(0.0, SimpleGarbler::new(1.0).unwrap())
+            .unwrap()
+            .with(1.0, SimpleGarbler::new(0.0).unwrap())
+            .unwrap();
+        // WHEN we garble values
+        let value = [true; 32].garble(&mut garbler);
+        // THEN the values should be the same as the original
+        assert_eq!(value, [true; 32]);
+    }
+
+    #[test]
+    fn test_weighted() {
+        // GIVEN a WeightedGarbler with a single garbler
+        let mut garbler = WeightedGarbler::new()
+            .with(0.3, SimpleGarbler::new(1.0).unwrap())
+            .unwrap();
+        // WHEN we garble a value
+        let value = true.garble(&mut garbler);
+        // THEN the value should be different
+        assert!(!value);
+    }
+
+    #[test]
+    fn test_invalid_weight() {
+        for weight in [-1.0, f64::INFINITY] {
+            let garbler = WeightedGarbler::new().with(weight, SimpleGarbler::new(1.0).unwrap());
+            assert_eq!(garbler.unwrap_err(), Error::InvalidWeight(weight));
+        }
+    }
+
+    #[test]
+    fn test_infinite_total_weight() {
+        // GIVEN a WeightedGarbler with the largest weight
+        let garbler = WeightedGarbler::new()
+            .with(f64::MAX, SimpleGarbler::new(1.0).unwrap())
+            .unwrap();
+        // WHEN we add another garbler with the largest weight
+        let garbler = garbler.with(f64::MAX, SimpleGarbler::new(1.0).unwrap());
+        // THEN it should fail, as the total weight would not be finite
+        assert_eq!(garbler.unwrap_err(), Error::InvalidWeight(f64::MAX));
+    }
+}
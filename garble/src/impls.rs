@@ -1,7 +1,18 @@
-use crate::{Garble, Garbler};
-use core::num;
+use crate::{Garble, GarbleInPlace, Garbler};
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::{borrow, boxed::Box, collections, ffi::CString, rc, string::String, vec::Vec};
+use core::{cell, cmp, ffi::CStr, marker, net, num, ops, sync::atomic, time::Duration};
 use paste::paste;
-use std::{collections, ffi, hash, marker, net, sync::atomic};
+#[cfg(feature = "std")]
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::{OsStr, OsString},
+    hash,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, PoisonError, RwLock},
+    time::{Instant, SystemTime},
+};
 
 /// Macro for creating [`Garble`] implementations with a closure.
 macro_rules! impl_garble {
@@ -79,6 +90,18 @@ macro_rules! impl_garble_atomic {
                 |s: Self, g: &mut G| $atomic::new(g.[<garble_ $primitive>](s.into_inner()))
             })
         ));
+
+        paste! {
+            impl GarbleInPlace for $atomic {
+                fn garble_in_place<G>(&self, garbler: &mut G)
+                where
+                    G: Garbler,
+                {
+                    let value = self.load(atomic::Ordering::SeqCst);
+                    self.store(garbler.[<garble_ $primitive>](value), atomic::Ordering::SeqCst);
+                }
+            }
+        }
     }
 }
 
@@ -105,17 +128,34 @@ impl_garble_primitive!(f32);
 impl_garble_primitive!(f64);
 impl_garble_primitive!(String => (String, str));
 impl_garble_primitive!(&str => (String, str));
+#[cfg(feature = "std")]
+impl_garble_primitive!(OsString => (OsString, os_str));
+#[cfg(feature = "std")]
+impl_garble_primitive!(&OsStr => (OsString, os_str));
+#[cfg(feature = "std")]
+impl_garble_primitive!(PathBuf => (PathBuf, path));
+#[cfg(feature = "std")]
+impl_garble_primitive!(&Path => (PathBuf, path));
+impl_garble_primitive!(Duration => (Duration, duration));
+#[cfg(feature = "std")]
+impl_garble_primitive!(SystemTime => (SystemTime, system_time));
+#[cfg(feature = "std")]
+impl_garble_primitive!(Instant => (Instant, instant));
 impl_garble_numeric!(bool, AT(atomic::AtomicBool));
 impl_garble_numeric!(u8, NZ(num::NonZeroU8), AT(atomic::AtomicU8));
 impl_garble_numeric!(u16, NZ(num::NonZeroU16), AT(atomic::AtomicU16));
 impl_garble_numeric!(u32, NZ(num::NonZeroU32), AT(atomic::AtomicU32));
-impl_garble_numeric!(u64, NZ(num::NonZeroU64), AT(atomic::AtomicU64));
+impl_garble_numeric!(u64, NZ(num::NonZeroU64));
+#[cfg(target_has_atomic = "64")]
+impl_garble_atomic!(u64, atomic::AtomicU64);
 impl_garble_numeric!(u128, NZ(num::NonZeroU128));
 impl_garble_numeric!(usize, NZ(num::NonZeroUsize), AT(atomic::AtomicUsize));
 impl_garble_numeric!(i8, NZ(num::NonZeroI8), AT(atomic::AtomicI8));
 impl_garble_numeric!(i16, NZ(num::NonZeroI16), AT(atomic::AtomicI16));
 impl_garble_numeric!(i32, NZ(num::NonZeroI32), AT(atomic::AtomicI32));
-impl_garble_numeric!(i64, NZ(num::NonZeroI64), AT(atomic::AtomicI64));
+impl_garble_numeric!(i64, NZ(num::NonZeroI64));
+#[cfg(target_has_atomic = "64")]
+impl_garble_atomic!(i64, atomic::AtomicI64);
 impl_garble_numeric!(i128, NZ(num::NonZeroI128));
 impl_garble_numeric!(isize, NZ(num::NonZeroIsize), AT(atomic::AtomicIsize));
 
@@ -161,6 +201,252 @@ impl_garble!(Result[T, E] => (
     })
 ));
 
+// Wrapping<T>
+impl_garble!(num::Wrapping[T] => (
+    num::Wrapping,
+    (|s: Self, g: &mut G| num::Wrapping(s.0.garble(g)))
+));
+
+// Saturating<T>
+impl_garble!(num::Saturating[T] => (
+    num::Saturating,
+    (|s: Self, g: &mut G| num::Saturating(s.0.garble(g)))
+));
+
+// Reverse<T>
+impl_garble!(cmp::Reverse[T] => (
+    cmp::Reverse,
+    (|s: Self, g: &mut G| cmp::Reverse(s.0.garble(g)))
+));
+
+// Ordering
+impl_garble!(cmp::Ordering => (
+    cmp::Ordering,
+    (|s: Self, g: &mut G| g.garble_i8(s as i8).cmp(&0))
+));
+
+///////////////////////////////////////////////////////////////////////////////
+// Garble implementations for ranges
+
+impl<T> Garble for ops::Range<T>
+where
+    T: Garble,
+    T::Output: Clone,
+{
+    type Output = ops::Range<T::Output>;
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        let (start, end) = garbler.garble_range(self.start, self.end);
+        start..end
+    }
+}
+
+impl<T> Garble for ops::RangeInclusive<T>
+where
+    T: Garble,
+    T::Output: PartialOrd,
+{
+    type Output = ops::RangeInclusive<T::Output>;
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        let (start, end) = self.into_inner();
+        let (start, end) = garbler.garble_range_inclusive(start, end);
+        start..=end
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Garble implementations for smart pointers
+
+// Box<T>
+impl_garble!(Box[T] => (
+    Box,
+    (|s: Self, g: &mut G| Box::new((*s).garble(g)))
+));
+
+impl<T> Garble for Box<[T]>
+where
+    T: Garble,
+{
+    type Output = Box<[T::Output]>;
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        self.into_vec()
+            .into_iter()
+            .map(|v| v.garble(garbler))
+            .collect()
+    }
+}
+
+impl_garble!(Box<str> => (
+    Box<str>,
+    (|s: Self, g: &mut G| g.garble_str(s).into_boxed_str())
+));
+
+/// Macro for creating [`Garble`] implementations for reference-counted
+/// pointers
+///
+/// If the pointer is shared, the inner value is cloned before garbling it, so
+/// other owners do not see the garbled value.
+macro_rules! impl_garble_refcounted {
+    ($type:ty) => {
+        paste! {
+            impl<T> Garble for $type<T>
+            where
+                T: Garble + Clone,
+            {
+                type Output = $type<T::Output>;
+
+                fn garble<G>(self, garbler: &mut G) -> Self::Output
+                where
+                    G: Garbler,
+                {
+                    let value = $type::try_unwrap(self).unwrap_or_else(|s| (*s).clone());
+                    $type::new(value.garble(garbler))
+                }
+            }
+        }
+    };
+}
+impl_garble_refcounted! { rc::Rc }
+#[cfg(target_has_atomic = "ptr")]
+impl_garble_refcounted! { Arc }
+
+// Cow<'_, B>
+impl<B> Garble for borrow::Cow<'_, B>
+where
+    B: borrow::ToOwned + ?Sized,
+    B::Owned: Garble<Output = B::Owned>,
+{
+    type Output = Self;
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        borrow::Cow::Owned(self.into_owned().garble(garbler))
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Garble implementations for interior mutability and synchronization types
+
+// Cell<T>
+impl_garble!(cell::Cell[T] => (
+    cell::Cell,
+    (|s: Self, g: &mut G| cell::Cell::new(s.into_inner().garble(g)))
+));
+
+impl<T> GarbleInPlace for cell::Cell<T>
+where
+    T: Garble<Output = T> + Copy,
+{
+    fn garble_in_place<G>(&self, garbler: &mut G)
+    where
+        G: Garbler,
+    {
+        self.set(self.get().garble(garbler));
+    }
+}
+
+// RefCell<T>
+impl_garble!(cell::RefCell[T] => (
+    cell::RefCell,
+    (|s: Self, g: &mut G| cell::RefCell::new(s.into_inner().garble(g)))
+));
+
+/// If the value is already borrowed, it is left untouched instead of
+/// panicking.
+impl<T> GarbleInPlace for cell::RefCell<T>
+where
+    T: Garble<Output = T> + Clone,
+{
+    fn garble_in_place<G>(&self, garbler: &mut G)
+    where
+        G: Garbler,
+    {
+        if let Ok(mut value) = self.try_borrow_mut() {
+            *value = value.clone().garble(garbler);
+        }
+    }
+}
+
+// Mutex<T>
+#[cfg(feature = "std")]
+impl_garble!(Mutex[T] => (
+    Mutex,
+    (|s: Self, g: &mut G| {
+        let value = s.into_inner().unwrap_or_else(PoisonError::into_inner);
+        Mutex::new(value.garble(g))
+    })
+));
+
+#[cfg(feature = "std")]
+impl<T> GarbleInPlace for Mutex<T>
+where
+    T: Garble<Output = T> + Clone,
+{
+    fn garble_in_place<G>(&self, garbler: &mut G)
+    where
+        G: Garbler,
+    {
+        let mut value = self.lock().unwrap_or_else(PoisonError::into_inner);
+        *value = value.clone().garble(garbler);
+    }
+}
+
+// RwLock<T>
+#[cfg(feature = "std")]
+impl_garble!(RwLock[T] => (
+    RwLock,
+    (|s: Self, g: &mut G| {
+        let value = s.into_inner().unwrap_or_else(PoisonError::into_inner);
+        RwLock::new(value.garble(g))
+    })
+));
+
+#[cfg(feature = "std")]
+impl<T> GarbleInPlace for RwLock<T>
+where
+    T: Garble<Output = T> + Clone,
+{
+    fn garble_in_place<G>(&self, garbler: &mut G)
+    where
+        G: Garbler,
+    {
+        let mut value = self.write().unwrap_or_else(PoisonError::into_inner);
+        *value = value.clone().garble(garbler);
+    }
+}
+
+// OnceCell<T>
+impl_garble!(cell::OnceCell[T] => (
+    cell::OnceCell,
+    (|s: Self, g: &mut G| {
+        s.into_inner()
+            .map_or_else(cell::OnceCell::new, |v| cell::OnceCell::from(v.garble(g)))
+    })
+));
+
+// OnceLock<T>
+#[cfg(feature = "std")]
+impl_garble!(OnceLock[T] => (
+    OnceLock,
+    (|s: Self, g: &mut G| {
+        s.into_inner()
+            .map_or_else(OnceLock::new, |v| OnceLock::from(v.garble(g)))
+    })
+));
+
 ///////////////////////////////////////////////////////////////////////////////
 // Garble implementations for arrays and slices
 
@@ -259,7 +545,8 @@ macro_rules! impl_garble_map {
     };
 }
 impl_garble_map!(collections::BTreeMap, Ord);
-impl_garble_map!(collections::HashMap, hash::Hash + Eq);
+#[cfg(feature = "std")]
+impl_garble_map!(HashMap, hash::Hash + Eq);
 
 ///////////////////////////////////////////////////////////////////////////////
 // Garble implementations for sets
@@ -285,27 +572,15 @@ macro_rules! impl_garble_set {
     };
 }
 impl_garble_set!(collections::BTreeSet, Ord);
-impl_garble_set!(collections::HashSet, hash::Hash + Eq);
+#[cfg(feature = "std")]
+impl_garble_set!(HashSet, hash::Hash + Eq);
 impl_garble_set!(collections::BinaryHeap, Ord);
 
 ///////////////////////////////////////////////////////////////////////////////
 // Garble implementation for IP addresses
 
-impl_garble!(net::Ipv4Addr => (
-    net::Ipv4Addr,
-    (|s: Self, garbler: &mut G| {
-        let [a, b, c, d] = s.octets().garble(garbler);
-        net::Ipv4Addr::new(a, b, c, d)
-    })
-));
-
-impl_garble!(net::Ipv6Addr => (
-    net::Ipv6Addr,
-    (|s: Self, garbler: &mut G| {
-        let [a, b, c, d, e, f, g, h] = s.segments().garble(garbler);
-        net::Ipv6Addr::new(a, b, c, d, e, f, g, h)
-    })
-));
+impl_garble_primitive!(net::Ipv4Addr => (net::Ipv4Addr, ipv4_addr));
+impl_garble_primitive!(net::Ipv6Addr => (net::Ipv6Addr, ipv6_addr));
 
 impl_garble!(net::IpAddr => (
     net::IpAddr,
@@ -320,27 +595,8 @@ impl_garble!(net::IpAddr => (
 ///////////////////////////////////////////////////////////////////////////////
 // Garble implementations for sockets
 
-impl_garble!(net::SocketAddrV4 => (
-    net::SocketAddrV4,
-    (|s: Self, garbler: &mut G| {
-        net::SocketAddrV4::new(
-            s.ip().garble(garbler),
-            s.port().garble(garbler),
-        )
-    })
-));
-
-impl_garble!(net::SocketAddrV6 => (
-    net::SocketAddrV6,
-    (|s: Self, garbler: &mut G| {
-        net::SocketAddrV6::new(
-            s.ip().garble(garbler),
-            s.port().garble(garbler),
-            s.flowinfo(),
-            s.scope_id(),
-        )
-    })
-));
+impl_garble_primitive!(net::SocketAddrV4 => (net::SocketAddrV4, socket_addr_v4));
+impl_garble_primitive!(net::SocketAddrV6 => (net::SocketAddrV6, socket_addr_v6));
 
 impl_garble!(net::SocketAddr => (
     net::SocketAddr,
@@ -355,19 +611,54 @@ impl_garble!(net::SocketAddr => (
 ///////////////////////////////////////////////////////////////////////////////
 // Garble implementations for C strings
 
-impl_garble!(ffi::CString => (
-    ffi::CString,
-    (|s: Self, garbler: &mut G| {
-        let bytes = s.to_bytes().iter().map(|b| match b.garble(garbler) {
-            // We cannot have nul bytes in a C string, so we replace them with
-            // a question mark.
-            0 => 0x3F,
-            b => b,
-        }).collect::<Vec<_>>();
-        ffi::CString::new(bytes).unwrap()
-    })
+impl_garble_primitive!(CString => (CString, c_str));
+impl_garble_primitive!(&CStr => (CString, c_str));
+
+impl_garble!(Box<CStr> => (
+    Box<CStr>,
+    (|s: Self, garbler: &mut G| CString::from(s).garble(garbler).into_boxed_c_str())
 ));
 
+///////////////////////////////////////////////////////////////////////////////
+// Garble implementation for JSON values
+
+#[cfg(feature = "serde_json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde_json")))]
+impl Garble for serde_json::Value {
+    type Output = Self;
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        use serde_json::{Number, Value};
+
+        let value = match self {
+            Value::Null => Value::Null,
+            Value::Bool(v) => Value::Bool(garbler.garble_bool(v)),
+            Value::Number(v) => Value::Number(if let Some(n) = v.as_u64() {
+                garbler.garble_u64(n).into()
+            } else if let Some(n) = v.as_i64() {
+                garbler.garble_i64(n).into()
+            } else {
+                // JSON cannot represent NaN or infinite numbers
+                v.as_f64()
+                    .and_then(|n| Number::from_f64(garbler.garble_f64(n)))
+                    .unwrap_or(v)
+            }),
+            Value::String(v) => Value::String(garbler.garble_str(v)),
+            Value::Array(v) => Value::Array(v.garble(garbler)),
+            // Keys can only be renamed as a structural fault
+            Value::Object(v) => Value::Object(
+                v.into_iter()
+                    .map(|(key, value)| (key, value.garble(garbler)))
+                    .collect(),
+            ),
+        };
+        garbler.garble_json_structure(value)
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 // Garble implementation for borrowed values
 
@@ -385,9 +676,64 @@ where
     }
 }
 
+// This also covers byte slices, which are garbled into a `Vec<u8>`.
+impl<T> Garble for &[T]
+where
+    T: Garble + Clone,
+{
+    type Output = Vec<T::Output>;
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        self.iter().map(|v| v.garble(garbler)).collect()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Garble implementation for mutable references
+//
+// These garble the referenced value in place and return the reference.
+
+impl<'a, T> Garble for &'a mut T
+where
+    T: Garble<Output = T> + Clone,
+{
+    type Output = &'a mut T;
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        *self = self.clone().garble(garbler);
+        self
+    }
+}
+
+impl<'a, T> Garble for &'a mut [T]
+where
+    T: Garble<Output = T> + Clone,
+{
+    type Output = &'a mut [T];
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        for v in self.iter_mut() {
+            v.garble(garbler);
+        }
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::{borrow::ToOwned, string::ToString, vec};
+    #[cfg(feature = "std")]
+    use std::time::UNIX_EPOCH;
 
     #[derive(Debug)]
     struct PassGarbler;
@@ -580,6 +926,19 @@ mod tests {
     // Bytes
     test_passthrough!(bytes, b"Hello, world!", b"Hello, world!".to_owned());
     test_passthrough!(bytes_owned, b"Hello, world!".to_owned());
+    test_passthrough!(
+        bytes_slice,
+        &b"Hello, world!"[..],
+        b"Hello, world!".to_vec()
+    );
+
+    // Slices
+    test_passthrough!(slice, &[1u32, 2, 3][..], vec![1u32, 2, 3]);
+    test_passthrough!(
+        slice_str,
+        &["a", "b"][..],
+        vec![String::from("a"), String::from("b")]
+    );
 
     // IP Addresses
     test_passthrough!(ipv4, net::Ipv4Addr::new(127, 0, 0, 1));
@@ -597,12 +956,126 @@ mod tests {
             8080
         )
     );
+    test_passthrough!(
+        socket_ipv6_scoped,
+        net::SocketAddrV6::new(
+            net::Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+            8080,
+            0x12345,
+            2
+        )
+    );
+
+    // Numeric and ordering wrappers
+    test_passthrough!(wrapping, num::Wrapping(1u32));
+    test_passthrough!(saturating, num::Saturating(1u32));
+    test_passthrough!(reverse, cmp::Reverse(1u32));
+    test_passthrough!(ordering_less, cmp::Ordering::Less);
+    test_passthrough!(ordering_equal, cmp::Ordering::Equal);
+    test_passthrough!(ordering_greater, cmp::Ordering::Greater);
+
+    // Ranges
+    test_passthrough!(range, 1u32..10);
+    test_passthrough!(range_inclusive, 1u32..=10);
+    test_passthrough!(range_str, "a".."z", String::from("a")..String::from("z"));
+
+    // OS strings and paths
+    #[cfg(feature = "std")]
+    test_passthrough!(os_string, OsString::from("Hello, world!"));
+    #[cfg(feature = "std")]
+    test_passthrough!(
+        os_str,
+        OsStr::new("Hello, world!"),
+        OsString::from("Hello, world!")
+    );
+    #[cfg(feature = "std")]
+    test_passthrough!(path_buf, PathBuf::from("/var/lib/data.bin"));
+    #[cfg(feature = "std")]
+    test_passthrough!(
+        path,
+        Path::new("/var/lib/data.bin"),
+        PathBuf::from("/var/lib/data.bin")
+    );
+
+    // Time types
+    test_passthrough!(duration, Duration::new(30, 500));
+    #[cfg(feature = "std")]
+    test_passthrough!(system_time, UNIX_EPOCH + Duration::new(1_000_000, 500));
+    #[cfg(feature = "std")]
+    test_passthrough!(
+        system_time_pre_epoch,
+        UNIX_EPOCH - Duration::new(1_000_000, 500)
+    );
+
+    // Smart pointers
+    test_passthrough!(boxed, Box::new(1u32));
+    test_passthrough!(boxed_str, Box::<str>::from("Hello, world!"));
+    test_passthrough!(boxed_slice, vec![1u32, 2, 3].into_boxed_slice());
+    test_passthrough!(rc, rc::Rc::new(1u32));
+    #[cfg(target_has_atomic = "ptr")]
+    test_passthrough!(arc, Arc::new(1u32));
+    test_passthrough!(
+        cow_borrowed,
+        borrow::Cow::Borrowed("Hello, world!"),
+        borrow::Cow::<str>::Owned(String::from("Hello, world!"))
+    );
+    test_passthrough!(cow_owned, borrow::Cow::<[u32]>::Owned(vec![1, 2, 3]));
+
+    #[test]
+    fn test_rc_shared() {
+        let mut garbler = PassGarbler;
+        let value = rc::Rc::new(String::from("Hello, world!"));
+        let shared = rc::Rc::clone(&value);
+        let garbled = value.garble(&mut garbler);
+        assert!(!rc::Rc::ptr_eq(&garbled, &shared));
+        assert_eq!(garbled, shared);
+    }
+
+    // Interior mutability and synchronization types
+    test_passthrough!(cell, cell::Cell::new(1u32));
+    test_passthrough!(refcell, cell::RefCell::new(String::from("Hello, world!")));
+    test_passthrough!(oncecell, cell::OnceCell::from(1u32));
+    test_passthrough!(oncecell_empty, cell::OnceCell::<u32>::new());
+    #[cfg(feature = "std")]
+    test_passthrough!(oncelock, OnceLock::from(1u32));
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_mutex() {
+        let mut garbler = PassGarbler;
+        let garbled = Mutex::new(1u32).garble(&mut garbler);
+        assert_eq!(garbled.into_inner().unwrap(), 1u32);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_rwlock() {
+        let mut garbler = PassGarbler;
+        let garbled = RwLock::new(1u32).garble(&mut garbler);
+        assert_eq!(garbled.into_inner().unwrap(), 1u32);
+    }
+
+    // JSON values
+    #[cfg(feature = "serde_json")]
+    test_passthrough!(
+        json_value,
+        serde_json::json!({"a": [1, -2, 3.5, "b", true, null], "c": {"d": "e"}})
+    );
 
     // CStrings
-    test_passthrough!(cstring, ffi::CString::new("Hello, world!").unwrap());
+    test_passthrough!(cstring, CString::new("Hello, world!").unwrap());
+    test_passthrough!(
+        boxed_cstr,
+        CString::new("Hello, world!").unwrap().into_boxed_c_str()
+    );
+    test_passthrough!(
+        cstr,
+        c"Hello, world!",
+        CString::new("Hello, world!").unwrap()
+    );
     test_passthrough!(
         borrowed_cstring,
-        &ffi::CString::new("Hello, world!").unwrap(),
-        ffi::CString::new("Hello, world!").unwrap()
+        &CString::new("Hello, world!").unwrap(),
+        CString::new("Hello, world!").unwrap()
     );
 }
@@ -0,0 +1,17 @@
+//! Wrappers that garble data flowing through I/O streams
+
+use core::time::Duration;
+
+mod read;
+pub use read::GarbleReader;
+
+#[cfg(feature = "tokio")]
+mod tokio;
+#[cfg(feature = "tokio")]
+pub use self::tokio::{GarbleAsyncRead, GarbleAsyncWrite};
+
+mod write;
+pub use write::GarbleWriter;
+
+/// Default duration of stalls
+const STALL_DURATION: Duration = Duration::from_millis(10);
@@ -0,0 +1,187 @@
+use super::STALL_DURATION;
+use crate::Garbler;
+use std::{
+    io::{self, Read},
+    thread,
+    time::Duration,
+};
+
+/// Number of faults that can be applied to a read
+const READ_FAULTS: usize = 4;
+
+/// [`Read`] wrapper that garbles bytes as they are read
+///
+/// Every byte read from the inner reader is garbled as a `u8`. On each call to
+/// [`read`](Read::read), one of the following faults can also be applied, as
+/// picked by [`Garbler::structural_fault`]:
+///
+/// * Short read, filling at most half of the buffer
+/// * [`Interrupted`](io::ErrorKind::Interrupted) error
+/// * [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) error
+/// * Stall, blocking the current thread before reading
+///
+/// Short reads and errors do not consume any data, so a reader that handles
+/// them correctly still sees every byte.
+///
+/// ```rust
+/// use garble::{GarbleReader, SimpleGarbler};
+/// use std::io::Read;
+///
+/// let garbler = SimpleGarbler::builder()
+///     .rate(0.01)
+///     .structural_rate(0.2)
+///     .build()
+///     .unwrap();
+/// let mut reader = GarbleReader::new(&b"hello, world"[..], garbler);
+///
+/// let mut output = Vec::new();
+/// dbg!(reader.read_to_end(&mut output), output);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct GarbleReader<R, G> {
+    inner: R,
+    garbler: G,
+    stall_duration: Duration,
+}
+
+impl<R, G> GarbleReader<R, G>
+where
+    R: Read,
+    G: Garbler,
+{
+    /// Create a new [`GarbleReader`] around the inner reader
+    pub fn new(inner: R, garbler: G) -> Self {
+        Self {
+            inner,
+            garbler,
+            stall_duration: STALL_DURATION,
+        }
+    }
+
+    /// Set the duration of stalls
+    ///
+    /// Defaults to 10 milliseconds.
+    pub fn stall_duration(mut self, duration: Duration) -> Self {
+        self.stall_duration = duration;
+        self
+    }
+
+    /// Reference to the inner reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Mutable reference to the inner reader
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Reference to the garbler
+    pub fn garbler(&self) -> &G {
+        &self.garbler
+    }
+
+    /// Consume this wrapper and return the inner reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, G> Read for GarbleReader<R, G>
+where
+    R: Read,
+    G: Garbler,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = match self.garbler.structural_fault(READ_FAULTS) {
+            // Short read
+            Some(0) => (buf.len() / 2).max(1).min(buf.len()),
+            Some(1) => return Err(io::ErrorKind::Interrupted.into()),
+            Some(2) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            // Stall
+            Some(_) => {
+                thread::sleep(self.stall_duration);
+                buf.len()
+            }
+            None => buf.len(),
+        };
+
+        let read = self.inner.read(&mut buf[..len])?;
+        for byte in &mut buf[..read] {
+            *byte = self.garbler.garble_u8(*byte);
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(all(test, feature = "simple"))]
+mod tests {
+    use super::*;
+    use crate::{CountingGarbler, SimpleGarbler};
+
+    const DATA: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+    #[test]
+    fn test_passthrough() {
+        // GIVEN a reader wrapped with a garbler that does not change anything
+        let mut reader = GarbleReader::new(DATA, CountingGarbler::new());
+        // WHEN we read everything
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        // THEN the data should be unchanged
+        assert_eq!(output, DATA);
+        // AND every byte should be garbled
+        assert_eq!(reader.garbler().count(), DATA.len());
+    }
+
+    #[test]
+    fn test_faults() {
+        // GIVEN a reader wrapped with a garbler that always applies faults,
+        // but never garbles bytes
+        let garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        let mut reader = GarbleReader::new(DATA, garbler).stall_duration(Duration::ZERO);
+        // WHEN we read everything, retrying on errors
+        let mut output = Vec::new();
+        let mut buf = [0; 8];
+        let mut errors = 0;
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => output.extend_from_slice(&buf[..read]),
+                Err(err) => {
+                    assert!(matches!(
+                        err.kind(),
+                        io::ErrorKind::Interrupted | io::ErrorKind::UnexpectedEof
+                    ));
+                    errors += 1;
+                }
+            }
+        }
+        // THEN no data should be lost
+        assert_eq!(output, DATA);
+        // AND some reads should have failed
+        assert!(errors > 0);
+    }
+
+    #[test]
+    fn test_garble_bytes() {
+        // GIVEN a reader wrapped with a garbler that always garbles bytes
+        let garbler = SimpleGarbler::builder()
+            .structural_rate(0.0)
+            .rate_for::<u8>(1.0)
+            .build()
+            .unwrap();
+        let mut reader = GarbleReader::new(DATA, garbler);
+        // WHEN we read everything
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        // THEN the data should have the same length, but be different
+        assert_eq!(output.len(), DATA.len());
+        assert_ne!(output, DATA);
+    }
+}
@@ -0,0 +1,566 @@
+use super::STALL_DURATION;
+use crate::Garbler;
+use ::tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    time::{self, Sleep},
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use pin_project_lite::pin_project;
+use std::io;
+
+/// Number of faults that can be applied to a read
+const READ_FAULTS: usize = 5;
+
+/// Number of faults that can be applied to a write
+const WRITE_FAULTS: usize = 7;
+
+/// Wake the task and return [`Poll::Pending`], as if the I/O was not ready
+fn pending<T>(cx: &mut Context<'_>) -> Poll<T> {
+    cx.waker().wake_by_ref();
+    Poll::Pending
+}
+
+/// Poll the current stall, if any
+fn poll_stall(stall: &mut Option<Pin<Box<Sleep>>>, cx: &mut Context<'_>) -> Poll<()> {
+    if let Some(sleep) = stall {
+        ready!(sleep.as_mut().poll(cx));
+        *stall = None;
+    }
+    Poll::Ready(())
+}
+
+pin_project! {
+    /// [`AsyncRead`] wrapper that garbles bytes as they are read
+    ///
+    /// This is the asynchronous version of a [`GarbleReader`](crate::GarbleReader).
+    /// Every byte read from the inner reader is garbled as a `u8`. On each call
+    /// to [`poll_read`](AsyncRead::poll_read), one of the following faults can
+    /// also be applied, as picked by [`Garbler::structural_fault`]:
+    ///
+    /// * Short read, filling at most half of the buffer
+    /// * [`Interrupted`](io::ErrorKind::Interrupted) error
+    /// * [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) error
+    /// * Stall, sleeping before reading
+    /// * [`Poll::Pending`], waking the task immediately
+    ///
+    /// The fault is picked once per read: if the inner reader is not ready, the
+    /// same fault is applied when it is polled again. Stalls use
+    /// [`tokio::time::sleep`](::tokio::time::sleep), so they complete without
+    /// waiting when the clock is paused in tests.
+    ///
+    /// ```rust
+    /// use garble::{GarbleAsyncRead, SimpleGarbler};
+    /// use tokio::io::AsyncReadExt;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let garbler = SimpleGarbler::builder()
+    ///     .rate(0.01)
+    ///     .structural_rate(0.2)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut reader = GarbleAsyncRead::new(&b"hello, world"[..], garbler);
+    ///
+    /// let mut output = Vec::new();
+    /// dbg!(reader.read_to_end(&mut output).await, output);
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    #[derive(Debug)]
+    pub struct GarbleAsyncRead<R, G> {
+        #[pin]
+        inner: R,
+        garbler: G,
+        stall_duration: Duration,
+        stall: Option<Pin<Box<Sleep>>>,
+        // Length of the read in progress, once its fault is picked
+        len: Option<usize>,
+    }
+}
+
+impl<R, G> GarbleAsyncRead<R, G>
+where
+    R: AsyncRead,
+    G: Garbler,
+{
+    /// Create a new [`GarbleAsyncRead`] around the inner reader
+    pub fn new(inner: R, garbler: G) -> Self {
+        Self {
+            inner,
+            garbler,
+            stall_duration: STALL_DURATION,
+            stall: None,
+            len: None,
+        }
+    }
+
+    /// Set the duration of stalls
+    ///
+    /// Defaults to 10 milliseconds.
+    pub fn stall_duration(mut self, duration: Duration) -> Self {
+        self.stall_duration = duration;
+        self
+    }
+
+    /// Reference to the inner reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Mutable reference to the inner reader
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Reference to the garbler
+    pub fn garbler(&self) -> &G {
+        &self.garbler
+    }
+
+    /// Consume this wrapper and return the inner reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, G> AsyncRead for GarbleAsyncRead<R, G>
+where
+    R: AsyncRead,
+    G: Garbler,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        // Faults are not picked again until the read in progress completes
+        if this.len.is_none() {
+            let mut len = buf.remaining();
+            match this.garbler.structural_fault(READ_FAULTS) {
+                // Short read
+                Some(0) => len = (len / 2).max(1).min(len),
+                Some(1) => return Poll::Ready(Err(io::ErrorKind::Interrupted.into())),
+                Some(2) => return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into())),
+                Some(3) => *this.stall = Some(Box::pin(time::sleep(*this.stall_duration))),
+                Some(_) => return pending(cx),
+                None => {}
+            }
+            *this.len = Some(len);
+        }
+        ready!(poll_stall(this.stall, cx));
+
+        let len = this.len.unwrap().min(buf.remaining());
+        let mut limited = ReadBuf::new(buf.initialize_unfilled_to(len));
+        let result = ready!(this.inner.poll_read(cx, &mut limited));
+        *this.len = None;
+        result?;
+        let read = limited.filled().len();
+        for byte in limited.filled_mut() {
+            *byte = this.garbler.garble_u8(*byte);
+        }
+        buf.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+pin_project! {
+    /// [`AsyncWrite`] wrapper that garbles bytes as they are written
+    ///
+    /// This is the asynchronous version of a [`GarbleWriter`](crate::GarbleWriter).
+    /// Every byte written to the inner writer is garbled as a `u8`. On each call
+    /// to [`poll_write`](AsyncWrite::poll_write), one of the following faults
+    /// can also be applied, as picked by [`Garbler::structural_fault`]:
+    ///
+    /// * Dropped buffer, reported as written without reaching the inner writer
+    /// * Duplicated buffer, written twice to the inner writer
+    /// * Partial write, writing at most half of the buffer
+    /// * [`WriteZero`](io::ErrorKind::WriteZero) error
+    /// * [`BrokenPipe`](io::ErrorKind::BrokenPipe) error
+    /// * Stall, sleeping before writing
+    /// * [`Poll::Pending`], waking the task immediately
+    ///
+    /// The fault is picked and the bytes are garbled once per write, and then
+    /// written in full to the inner writer, even if it is not ready or only
+    /// accepts part of them. Until then, polling again continues the same write
+    /// and ignores the buffer, which should be the same as in the first call.
+    ///
+    /// On each call to [`poll_flush`](AsyncWrite::poll_flush), the flush can
+    /// fail without flushing the inner writer. Stalls use
+    /// [`tokio::time::sleep`](::tokio::time::sleep), so they complete without
+    /// waiting when the clock is paused in tests.
+    ///
+    /// ```rust
+    /// use garble::{GarbleAsyncWrite, SimpleGarbler};
+    /// use tokio::io::AsyncWriteExt;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let garbler = SimpleGarbler::builder()
+    ///     .rate(0.01)
+    ///     .structural_rate(0.2)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut writer = GarbleAsyncWrite::new(Vec::new(), garbler);
+    ///
+    /// dbg!(writer.write_all(b"hello, world").await, writer.flush().await);
+    /// dbg!(writer.into_inner());
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    #[derive(Debug)]
+    pub struct GarbleAsyncWrite<W, G> {
+        #[pin]
+        inner: W,
+        garbler: G,
+        stall_duration: Duration,
+        stall: Option<Pin<Box<Sleep>>>,
+        write: Option<PendingWrite>,
+    }
+}
+
+/// Garbled bytes of a write in progress
+#[derive(Debug)]
+struct PendingWrite {
+    garbled: Vec<u8>,
+    /// Number of garbled bytes already written to the inner writer
+    written: usize,
+    /// Number of bytes reported as written once complete
+    len: usize,
+}
+
+impl<W, G> GarbleAsyncWrite<W, G>
+where
+    W: AsyncWrite,
+    G: Garbler,
+{
+    /// Create a new [`GarbleAsyncWrite`] around the inner writer
+    pub fn new(inner: W, garbler: G) -> Self {
+        Self {
+            inner,
+            garbler,
+            stall_duration: STALL_DURATION,
+            stall: None,
+            write: None,
+        }
+    }
+
+    /// Set the duration of stalls
+    ///
+    /// Defaults to 10 milliseconds.
+    pub fn stall_duration(mut self, duration: Duration) -> Self {
+        self.stall_duration = duration;
+        self
+    }
+
+    /// Reference to the inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Mutable reference to the inner writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Reference to the garbler
+    pub fn garbler(&self) -> &G {
+        &self.garbler
+    }
+
+    /// Consume this wrapper and return the inner writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W, G> AsyncWrite for GarbleAsyncWrite<W, G>
+where
+    W: AsyncWrite,
+    G: Garbler,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut this = self.project();
+        // Faults are not picked again until the write in progress completes
+        if this.write.is_none() {
+            let mut len = buf.len();
+            let mut duplicate = false;
+            match this.garbler.structural_fault(WRITE_FAULTS) {
+                // Dropped buffer
+                Some(0) => return Poll::Ready(Ok(buf.len())),
+                // Duplicated buffer
+                Some(1) => duplicate = true,
+                // Partial write, which still writes at least one byte
+                Some(2) => len = (len / 2).max(1).min(len),
+                Some(3) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Some(4) => return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+                Some(5) => *this.stall = Some(Box::pin(time::sleep(*this.stall_duration))),
+                Some(_) => return pending(cx),
+                None => {}
+            }
+
+            let mut garbled: Vec<u8> = buf[..len]
+                .iter()
+                .map(|b| this.garbler.garble_u8(*b))
+                .collect();
+            if duplicate {
+                garbled.extend_from_within(..);
+            }
+            *this.write = Some(PendingWrite {
+                garbled,
+                written: 0,
+                len,
+            });
+        }
+        ready!(poll_stall(this.stall, cx));
+
+        let write = this.write.as_mut().unwrap();
+        while write.written < write.garbled.len() {
+            let result = ready!(this
+                .inner
+                .as_mut()
+                .poll_write(cx, &write.garbled[write.written..]));
+            match result {
+                Ok(0) => {
+                    *this.write = None;
+                    return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+                }
+                Ok(written) => write.written += written,
+                Err(err) => {
+                    *this.write = None;
+                    return Poll::Ready(Err(err));
+                }
+            }
+        }
+        let len = write.len;
+        *this.write = None;
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.project();
+        match this.garbler.structural_fault(1) {
+            Some(_) => Poll::Ready(Err(io::Error::other("injected flush failure"))),
+            None => this.inner.poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+#[cfg(all(test, feature = "simple"))]
+mod tests {
+    use super::*;
+    use crate::{CountingGarbler, SimpleGarbler};
+    use ::tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        time::Instant,
+    };
+
+    const DATA: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+    /// Garbler that always applies structural faults, but never garbles bytes
+    fn faulty() -> SimpleGarbler {
+        SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap()
+    }
+
+    /// Inner reader or writer that is only ready on every other poll, and
+    /// accepts at most 4 bytes per write
+    struct Flaky<T> {
+        inner: T,
+        ready: bool,
+    }
+
+    impl<T> Flaky<T> {
+        fn new(inner: T) -> Self {
+            Self {
+                inner,
+                ready: false,
+            }
+        }
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+            self.ready = !self.ready;
+            match self.ready {
+                true => Poll::Ready(()),
+                false => pending(cx),
+            }
+        }
+    }
+
+    impl<T> AsyncRead for Flaky<T>
+    where
+        T: AsyncRead + Unpin,
+    {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            ready!(self.poll_ready(cx));
+            Pin::new(&mut self.inner).poll_read(cx, buf)
+        }
+    }
+
+    impl<T> AsyncWrite for Flaky<T>
+    where
+        T: AsyncWrite + Unpin,
+    {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            ready!(self.poll_ready(cx));
+            let len = buf.len().min(4);
+            Pin::new(&mut self.inner).poll_write(cx, &buf[..len])
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.inner).poll_shutdown(cx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_passthrough() {
+        // GIVEN a reader wrapped with a garbler that does not change anything
+        let mut reader = GarbleAsyncRead::new(DATA, CountingGarbler::new());
+        // WHEN we read everything
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).await.unwrap();
+        // THEN the data should be unchanged
+        assert_eq!(output, DATA);
+        // AND every byte should be garbled
+        assert_eq!(reader.garbler().count(), DATA.len());
+    }
+
+    #[tokio::test]
+    async fn test_read_pending() {
+        // GIVEN a reader that is not always ready, wrapped with a garbler that
+        // does not change anything
+        let mut reader = GarbleAsyncRead::new(Flaky::new(DATA), CountingGarbler::new());
+        // WHEN we read everything
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).await.unwrap();
+        // THEN the data should be unchanged
+        assert_eq!(output, DATA);
+        // AND every byte should be garbled once
+        assert_eq!(reader.garbler().count(), DATA.len());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_read_faults() {
+        // GIVEN a reader with long stalls and a garbler that always applies
+        // faults
+        let mut reader =
+            GarbleAsyncRead::new(DATA, faulty()).stall_duration(Duration::from_secs(3600));
+        // WHEN we read everything, retrying on errors
+        let start = Instant::now();
+        let mut output = Vec::new();
+        let mut buf = [0; 1];
+        loop {
+            match reader.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(read) => output.extend_from_slice(&buf[..read]),
+                Err(err) => assert!(matches!(
+                    err.kind(),
+                    io::ErrorKind::Interrupted | io::ErrorKind::UnexpectedEof
+                )),
+            }
+        }
+        // THEN no data should be lost
+        assert_eq!(output, DATA);
+        // AND the paused clock should have advanced through the stalls
+        assert!(start.elapsed() >= Duration::from_secs(3600));
+    }
+
+    #[tokio::test]
+    async fn test_write_passthrough() {
+        // GIVEN a writer wrapped with a garbler that does not change anything
+        let mut writer = GarbleAsyncWrite::new(Vec::new(), CountingGarbler::new());
+        // WHEN we write everything
+        writer.write_all(DATA).await.unwrap();
+        writer.flush().await.unwrap();
+        // THEN every byte should be garbled
+        assert_eq!(writer.garbler().count(), DATA.len());
+        // AND the data should be unchanged
+        assert_eq!(writer.into_inner(), DATA);
+    }
+
+    #[tokio::test]
+    async fn test_write_pending() {
+        // GIVEN a writer that is not always ready and accepts short writes,
+        // wrapped with a garbler that does not change anything
+        let mut writer = GarbleAsyncWrite::new(Flaky::new(Vec::new()), CountingGarbler::new());
+        // WHEN we write everything
+        writer.write_all(DATA).await.unwrap();
+        // THEN every byte should be garbled once
+        assert_eq!(writer.garbler().count(), DATA.len());
+        // AND the data should be unchanged
+        assert_eq!(writer.into_inner().inner, DATA);
+    }
+
+    #[tokio::test]
+    async fn test_write_duplicate_pending() {
+        // GIVEN a writer that is not always ready and accepts short writes,
+        // wrapped with a garbler that always applies faults
+        let mut writer = GarbleAsyncWrite::new(Flaky::new(Vec::new()), faulty());
+        // WHEN we write until a buffer is duplicated
+        for _ in 0..1000 {
+            if let Ok(written) = writer.write(DATA).await {
+                if writer.get_ref().inner.len() == 2 * DATA.len() {
+                    // THEN the whole buffer should be written twice, but
+                    // reported once
+                    assert_eq!(written, DATA.len());
+                    return;
+                }
+            }
+            writer.get_mut().inner.clear();
+        }
+        panic!("no buffer was duplicated");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_write_faults() {
+        // GIVEN a writer with long stalls and a garbler that always applies
+        // faults
+        let mut writer =
+            GarbleAsyncWrite::new(Vec::new(), faulty()).stall_duration(Duration::from_secs(3600));
+        // WHEN we write byte by byte
+        let start = Instant::now();
+        for chunk in DATA.repeat(3).chunks(1) {
+            match writer.write(chunk).await {
+                // Partial writes should still make progress
+                Ok(written) => assert!((1..=chunk.len()).contains(&written)),
+                Err(err) => assert!(matches!(
+                    err.kind(),
+                    io::ErrorKind::WriteZero | io::ErrorKind::BrokenPipe
+                )),
+            }
+        }
+        // THEN the paused clock should have advanced through the stalls
+        assert!(start.elapsed() >= Duration::from_secs(3600));
+        // AND flushing should always fail
+        assert!(writer.flush().await.is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::Garbler;
+use alloc::vec::Vec;
+use std::io::{self, Write};
+
+/// Number of faults that can be applied to a write
+const WRITE_FAULTS: usize = 5;
+
+/// [`Write`] wrapper that garbles bytes as they are written
+///
+/// Every byte written to the inner writer is garbled as a `u8`. On each call to
+/// [`write`](Write::write), one of the following faults can also be applied, as
+/// picked by [`Garbler::structural_fault`]:
+///
+/// * Dropped buffer, reported as written without reaching the inner writer
+/// * Duplicated buffer, written twice to the inner writer
+/// * Partial write, writing at most half of the buffer
+/// * [`WriteZero`](io::ErrorKind::WriteZero) error
+/// * [`BrokenPipe`](io::ErrorKind::BrokenPipe) error
+///
+/// The bytes of each write are garbled once and written in full to the inner
+/// writer, even if it only accepts part of them, so they are not garbled again
+/// when retried.
+///
+/// On each call to [`flush`](Write::flush), the flush can fail without
+/// flushing the inner writer.
+///
+/// ```rust
+/// use garble::{GarbleWriter, SimpleGarbler};
+/// use std::io::Write;
+///
+/// let garbler = SimpleGarbler::builder()
+///     .rate(0.01)
+///     .structural_rate(0.2)
+///     .build()
+///     .unwrap();
+/// let mut writer = GarbleWriter::new(Vec::new(), garbler);
+///
+/// dbg!(writer.write_all(b"hello, world"), writer.flush());
+/// dbg!(writer.into_inner());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct GarbleWriter<W, G> {
+    inner: W,
+    garbler: G,
+}
+
+impl<W, G> GarbleWriter<W, G>
+where
+    W: Write,
+    G: Garbler,
+{
+    /// Create a new [`GarbleWriter`] around the inner writer
+    pub fn new(inner: W, garbler: G) -> Self {
+        Self { inner, garbler }
+    }
+
+    /// Reference to the inner writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Mutable reference to the inner writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Reference to the garbler
+    pub fn garbler(&self) -> &G {
+        &self.garbler
+    }
+
+    /// Consume this wrapper and return the inner writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn garble_bytes(&mut self, buf: &[u8]) -> Vec<u8> {
+        buf.iter().map(|b| self.garbler.garble_u8(*b)).collect()
+    }
+}
+
+impl<W, G> Write for GarbleWriter<W, G>
+where
+    W: Write,
+    G: Garbler,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.garbler.structural_fault(WRITE_FAULTS) {
+            // Dropped buffer
+            Some(0) => Ok(buf.len()),
+            // Duplicated buffer
+            Some(1) => {
+                let garbled = self.garble_bytes(buf);
+                self.inner.write_all(&garbled)?;
+                self.inner.write_all(&garbled)?;
+                Ok(buf.len())
+            }
+            // Partial write, which still writes at least one byte
+            Some(2) => {
+                let len = (buf.len() / 2).max(1).min(buf.len());
+                let garbled = self.garble_bytes(&buf[..len]);
+                self.inner.write_all(&garbled)?;
+                Ok(len)
+            }
+            Some(3) => Err(io::ErrorKind::WriteZero.into()),
+            Some(_) => Err(io::ErrorKind::BrokenPipe.into()),
+            None => {
+                let garbled = self.garble_bytes(buf);
+                self.inner.write_all(&garbled)?;
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.garbler.structural_fault(1) {
+            Some(_) => Err(io::Error::other("injected flush failure")),
+            None => self.inner.flush(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "simple"))]
+mod tests {
+    use super::*;
+    use crate::{CountingGarbler, SimpleGarbler};
+
+    const DATA: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+    #[test]
+    fn test_passthrough() {
+        // GIVEN a writer wrapped with a garbler that does not change anything
+        let mut writer = GarbleWriter::new(Vec::new(), CountingGarbler::new());
+        // WHEN we write everything
+        writer.write_all(DATA).unwrap();
+        writer.flush().unwrap();
+        // THEN every byte should be garbled
+        assert_eq!(writer.garbler().count(), DATA.len());
+        // AND the data should be unchanged
+        assert_eq!(writer.into_inner(), DATA);
+    }
+
+    /// Inner writer that accepts at most 4 bytes per write
+    struct Short(Vec<u8>);
+
+    impl Write for Short {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(&buf[..buf.len().min(4)])
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_short_inner_write() {
+        // GIVEN a writer that accepts short writes, wrapped with a garbler
+        // that does not change anything
+        let mut writer = GarbleWriter::new(Short(Vec::new()), CountingGarbler::new());
+        // WHEN we write everything
+        writer.write_all(DATA).unwrap();
+        // THEN every byte should be garbled once
+        assert_eq!(writer.garbler().count(), DATA.len());
+        // AND the data should be unchanged
+        assert_eq!(writer.into_inner().0, DATA);
+    }
+
+    #[test]
+    fn test_faults() {
+        // GIVEN a writer wrapped with a garbler that always applies faults,
+        // but never garbles bytes
+        let garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        let mut writer = GarbleWriter::new(Vec::new(), garbler);
+        // WHEN we write and flush
+        let mut errors = 0;
+        for chunk in DATA.chunks(1) {
+            match writer.write(chunk) {
+                // Partial writes should still make progress
+                Ok(written) => assert!((1..=chunk.len()).contains(&written)),
+                Err(err) => {
+                    assert!(matches!(
+                        err.kind(),
+                        io::ErrorKind::WriteZero | io::ErrorKind::BrokenPipe
+                    ));
+                    errors += 1;
+                }
+            }
+        }
+        // THEN some writes should have failed
+        assert!(errors > 0);
+        // AND flushing should always fail
+        assert!(writer.flush().is_err());
+    }
+
+    #[test]
+    fn test_partial_write() {
+        // GIVEN a writer wrapped with a garbler that always applies faults
+        let garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        let mut writer = GarbleWriter::new(Vec::new(), garbler);
+        // WHEN we write until a partial write happens
+        let written = loop {
+            if let Ok(written) = writer.write(DATA) {
+                if written != DATA.len() {
+                    break written;
+                }
+            }
+        };
+        // THEN it should write at most half of the buffer
+        assert!(written <= DATA.len() / 2);
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{Garble, Garbler};
+use alloc::collections::VecDeque;
+use core::fmt;
+
+/// Number of faults that can be applied to an item
+const ITEM_FAULTS: usize = 4;
+
+/// Default size of the window for delayed items
+const WINDOW: usize = 4;
+
+/// Bounded window of items waiting to be yielded
+///
+/// Items that are delayed or reordered are held back until enough items were
+/// yielded after them. This is shared between [`Garbled`] and
+/// [`GarbledStream`](crate::GarbledStream).
+pub(crate) struct Window<T> {
+    /// Items ready to be yielded, in order
+    ready: VecDeque<T>,
+    /// Items held back, with the number of items to yield before them
+    delayed: VecDeque<(usize, T)>,
+    size: usize,
+}
+
+impl<T> Window<T>
+where
+    T: Clone,
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            ready: VecDeque::new(),
+            delayed: VecDeque::new(),
+            size: WINDOW,
+        }
+    }
+
+    /// Set the maximum number of items held back
+    pub(crate) fn set_size(&mut self, size: usize) {
+        self.size = size.max(1);
+    }
+
+    /// Next item ready to be yielded
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        self.ready.pop_front()
+    }
+
+    /// Add a garbled item, applying a fault picked by the garbler
+    pub(crate) fn push<G: Garbler>(&mut self, item: T, garbler: &mut G) {
+        let fault = garbler.structural_fault(ITEM_FAULTS);
+        self.apply(item, fault);
+    }
+
+    /// Add an item with the given fault
+    fn apply(&mut self, item: T, fault: Option<usize>) {
+        match fault {
+            // Dropped item
+            Some(0) => {}
+            // Duplicated item
+            Some(1) => {
+                self.release(item.clone());
+                self.release(item);
+            }
+            // Delayed item, yielded after a full window of items
+            Some(2) => self.delayed.push_back((self.size, item)),
+            // Reordered item, swapped with the next one
+            Some(_) => self.delayed.push_back((1, item)),
+            None => self.release(item),
+        }
+
+        // Keep the window bounded
+        while self.delayed.len() > self.size {
+            if let Some((_, item)) = self.delayed.pop_front() {
+                self.ready.push_back(item);
+            }
+        }
+    }
+
+    /// Mark an item as ready, and count it for the items held back
+    fn release(&mut self, item: T) {
+        self.ready.push_back(item);
+
+        let mut index = 0;
+        while index < self.delayed.len() {
+            self.delayed[index].0 -= 1;
+            if self.delayed[index].0 == 0 {
+                if let Some((_, item)) = self.delayed.remove(index) {
+                    self.ready.push_back(item);
+                }
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Release all items held back, once there are no more items to add
+    pub(crate) fn flush(&mut self) {
+        self.ready
+            .extend(self.delayed.drain(..).map(|(_, item)| item));
+    }
+}
+
+impl<T> fmt::Debug for Window<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Window")
+            .field("ready", &self.ready.len())
+            .field("delayed", &self.delayed.len())
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+/// Iterator that garbles items and simulates a lossy message stream
+///
+/// Created by [`GarbleIterator::garbled`]. Every item is garbled with
+/// [`Garble::garble`]. One of the following faults can also be applied to each
+/// item, as picked by [`Garbler::structural_fault`]:
+///
+/// * Dropped item
+/// * Duplicated item
+/// * Delayed item, yielded after a full window of items
+/// * Reordered item, swapped with the next one
+///
+/// Items held back are yielded once the inner iterator is exhausted, so no
+/// item is lost unless it was dropped.
+#[derive(Debug)]
+pub struct Garbled<I, G>
+where
+    I: Iterator,
+    I::Item: Garble,
+{
+    inner: I,
+    garbler: G,
+    window: Window<<I::Item as Garble>::Output>,
+}
+
+impl<I, G> Garbled<I, G>
+where
+    I: Iterator,
+    I::Item: Garble,
+    <I::Item as Garble>::Output: Clone,
+    G: Garbler,
+{
+    /// Create a new [`Garbled`] iterator around the inner iterator
+    pub fn new(inner: I, garbler: G) -> Self {
+        Self {
+            inner,
+            garbler,
+            window: Window::new(),
+        }
+    }
+
+    /// Set the size of the window for delayed items
+    ///
+    /// This is the number of items a delayed item is held back for, and the
+    /// maximum number of items held back at once. Defaults to 4.
+    pub fn window(mut self, size: usize) -> Self {
+        self.window.set_size(size);
+        self
+    }
+
+    /// Reference to the inner iterator
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Mutable reference to the inner iterator
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.inner
+    }
+
+    /// Reference to the garbler
+    pub fn garbler(&self) -> &G {
+        &self.garbler
+    }
+
+    /// Consume the [`Garbled`] iterator, returning the inner iterator
+    ///
+    /// Items held back are lost.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, G> Iterator for Garbled<I, G>
+where
+    I: Iterator,
+    I::Item: Garble,
+    <I::Item as Garble>::Output: Clone,
+    G: Garbler,
+{
+    type Item = <I::Item as Garble>::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.window.pop() {
+                return Some(item);
+            }
+            match self.inner.next() {
+                Some(item) => {
+                    let item = item.garble(&mut self.garbler);
+                    self.window.push(item, &mut self.garbler);
+                }
+                None => {
+                    self.window.flush();
+                    return self.window.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Extension trait to garble the items of an [`Iterator`]
+///
+/// ```rust
+/// use garble::{GarbleIterator, SimpleGarbler};
+///
+/// let mut garbler = SimpleGarbler::builder()
+///     .rate(0.1)
+///     .structural_rate(0.2)
+///     .build()
+///     .unwrap();
+/// let messages = (0..10u32).garbled(&mut garbler).collect::<Vec<_>>();
+/// dbg!(messages);
+/// ```
+pub trait GarbleIterator: Iterator {
+    /// Garble each item, and drop, duplicate, delay or reorder items
+    ///
+    /// See [`Garbled`] for more details.
+    fn garbled<G>(self, garbler: G) -> Garbled<Self, G>
+    where
+        Self: Sized,
+        Self::Item: Garble,
+        <Self::Item as Garble>::Output: Clone,
+        G: Garbler,
+    {
+        Garbled::new(self, garbler)
+    }
+}
+
+impl<I> GarbleIterator for I where I: Iterator {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::CountingGarbler;
+
+    /// Apply faults to items, returning the order in which they are yielded
+    fn apply(size: usize, items: &[(u32, Option<usize>)]) -> Vec<u32> {
+        let mut window = Window::new();
+        window.set_size(size);
+        let mut output = Vec::new();
+        for &(item, fault) in items {
+            window.apply(item, fault);
+            output.extend(core::iter::from_fn(|| window.pop()));
+        }
+        window.flush();
+        output.extend(core::iter::from_fn(|| window.pop()));
+        output
+    }
+
+    #[test]
+    fn test_window_faults() {
+        let cases = [
+            // Dropped item
+            (4, vec![(1, Some(0)), (2, None)], vec![2]),
+            // Duplicated item
+            (4, vec![(1, Some(1)), (2, None)], vec![1, 1, 2]),
+            // Delayed item
+            (
+                2,
+                vec![(1, Some(2)), (2, None), (3, None), (4, None)],
+                vec![2, 3, 1, 4],
+            ),
+            // Reordered item
+            (4, vec![(1, Some(3)), (2, None), (3, None)], vec![2, 1, 3]),
+            // Bounded window
+            (1, vec![(1, Some(2)), (2, Some(2))], vec![1, 2]),
+            // Items held back at the end
+            (4, vec![(1, None), (2, Some(2))], vec![1, 2]),
+        ];
+        for (size, items, expected) in cases {
+            // GIVEN items with faults
+            // WHEN we add them to a window
+            let output = apply(size, &items);
+            // THEN they should be yielded in the expected order
+            assert_eq!(output, expected, "items: {items:?}");
+        }
+    }
+
+    #[test]
+    fn test_passthrough() {
+        // GIVEN a garbler that does not change anything
+        let mut garbler = CountingGarbler::new();
+        // WHEN we garble an iterator
+        let items = (0..5u32).garbled(&mut garbler).collect::<Vec<_>>();
+        // THEN the items should be unchanged
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+        // AND every item should be garbled
+        assert_eq!(garbler.count(), 5);
+    }
+
+    #[cfg(feature = "simple")]
+    #[test]
+    fn test_faults() {
+        use crate::SimpleGarbler;
+
+        // GIVEN a garbler that always applies structural faults, but never
+        // garbles values
+        let garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble an iterator
+        let input = (0..100u32).collect::<Vec<_>>();
+        let output = input
+            .iter()
+            .copied()
+            .garbled(garbler)
+            .window(2)
+            .collect::<Vec<_>>();
+        // THEN the items should differ
+        assert_ne!(output, input);
+        // AND every item should come from the input
+        assert!(output.iter().all(|item| input.contains(item)));
+    }
+}
@@ -1,20 +1,71 @@
 #![warn(missing_debug_implementations, missing_docs, unreachable_pub)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![doc = include_str!("../../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod error;
+pub use crate::error::Error;
+
+pub mod formats;
 
 mod garble;
-pub use crate::garble::Garble;
+pub use crate::garble::{Garble, GarbleInPlace};
 
 mod impls;
 
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use crate::io::{GarbleAsyncRead, GarbleAsyncWrite};
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use crate::io::{GarbleReader, GarbleWriter};
+
+mod iter;
+pub use crate::iter::{GarbleIterator, Garbled};
+
+mod mutation;
+pub use crate::mutation::{Mutation, Primitive, PrimitiveKind, PrimitiveType};
+
+#[cfg(feature = "prost")]
+mod prost;
+#[cfg(feature = "prost")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
+pub use crate::prost::{garble_prost, garble_prost_wire};
+
+#[cfg(feature = "serde")]
+mod serde;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use crate::serde::{garble_serde, GarblingCompound, GarblingDeserializer, GarblingSerializer};
+
+mod shrink;
+pub use crate::shrink::{shrink, Shrunk};
+
+#[cfg(feature = "futures")]
+mod stream;
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub use crate::stream::{GarbleStream, GarbledStream};
+
 mod garbler;
-pub use crate::garbler::Garbler;
 #[cfg(feature = "simple")]
 #[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
-pub use crate::garbler::SimpleGarbler;
+pub use crate::garbler::WeightedGarbler;
+pub use crate::garbler::{
+    ChainGarbler, CountingGarbler, DynGarbler, Garbler, RecordingGarbler, ReplayGarbler,
+    SingleFaults, TargetedGarbler,
+};
+#[cfg(feature = "simple")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simple")))]
+pub use crate::garbler::{SimpleGarbler, SimpleGarblerBuilder};
 
 #[cfg(feature = "derive")]
 #[allow(unused_imports)]
 #[macro_use]
 extern crate garble_derive;
+#[cfg(feature = "derive")]
 pub use garble_derive::*;
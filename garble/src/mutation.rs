@@ -0,0 +1,169 @@
+use alloc::{ffi::CString, string::String};
+use core::{
+    ffi::CStr,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+use paste::paste;
+#[cfg(feature = "std")]
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+macro_rules! impl_primitive {
+    ($($(#[$meta:meta])* $t:ty),*) => {
+        paste! {
+            /// Primitive value passed through a [`Garbler`](crate::Garbler)
+            #[derive(Clone, Debug, PartialEq)]
+            #[allow(missing_docs)]
+            pub enum Primitive {
+                $($(#[$meta])* [<$t:camel>]($t),)*
+                Str(String),
+                CStr(CString),
+                #[cfg(feature = "std")]
+                OsStr(OsString),
+                #[cfg(feature = "std")]
+                Path(PathBuf),
+            }
+
+            /// Kind of primitive value passed through a [`Garbler`](crate::Garbler)
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            #[allow(missing_docs)]
+            pub enum PrimitiveKind {
+                $($(#[$meta])* [<$t:camel>],)*
+                Str,
+                CStr,
+                #[cfg(feature = "std")]
+                OsStr,
+                #[cfg(feature = "std")]
+                Path,
+            }
+
+            impl PrimitiveKind {
+                /// Number of primitive kinds
+                pub const COUNT: usize = [
+                    $($(#[$meta])* PrimitiveKind::[<$t:camel>],)*
+                    PrimitiveKind::Str,
+                    PrimitiveKind::CStr,
+                    #[cfg(feature = "std")]
+                    PrimitiveKind::OsStr,
+                    #[cfg(feature = "std")]
+                    PrimitiveKind::Path,
+                ]
+                .len();
+            }
+
+            impl Primitive {
+                /// Kind of this primitive value
+                pub fn kind(&self) -> PrimitiveKind {
+                    match self {
+                        $($(#[$meta])* Self::[<$t:camel>](_) => PrimitiveKind::[<$t:camel>],)*
+                        Self::Str(_) => PrimitiveKind::Str,
+                        Self::CStr(_) => PrimitiveKind::CStr,
+                        #[cfg(feature = "std")]
+                        Self::OsStr(_) => PrimitiveKind::OsStr,
+                        #[cfg(feature = "std")]
+                        Self::Path(_) => PrimitiveKind::Path,
+                    }
+                }
+            }
+
+            $(
+                $(#[$meta])*
+                impl From<$t> for Primitive {
+                    fn from(value: $t) -> Self {
+                        Self::[<$t:camel>](value)
+                    }
+                }
+
+                $(#[$meta])*
+                impl PrimitiveType for $t {
+                    const KIND: PrimitiveKind = PrimitiveKind::[<$t:camel>];
+                }
+            )*
+        }
+    };
+}
+
+/// Primitive type that a [`Garbler`](crate::Garbler) can garble directly
+pub trait PrimitiveType {
+    /// Kind of this primitive type
+    const KIND: PrimitiveKind;
+}
+
+impl_primitive! {
+    bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, Duration,
+    #[cfg(feature = "std")] SystemTime, #[cfg(feature = "std")] Instant, Ipv4Addr, Ipv6Addr,
+    SocketAddrV4, SocketAddrV6
+}
+
+impl From<String> for Primitive {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<CString> for Primitive {
+    fn from(value: CString) -> Self {
+        Self::CStr(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<OsString> for Primitive {
+    fn from(value: OsString) -> Self {
+        Self::OsStr(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<PathBuf> for Primitive {
+    fn from(value: PathBuf) -> Self {
+        Self::Path(value)
+    }
+}
+
+impl PrimitiveType for str {
+    const KIND: PrimitiveKind = PrimitiveKind::Str;
+}
+
+impl PrimitiveType for CStr {
+    const KIND: PrimitiveKind = PrimitiveKind::CStr;
+}
+
+#[cfg(feature = "std")]
+impl PrimitiveType for OsStr {
+    const KIND: PrimitiveKind = PrimitiveKind::OsStr;
+}
+
+#[cfg(feature = "std")]
+impl PrimitiveType for Path {
+    const KIND: PrimitiveKind = PrimitiveKind::Path;
+}
+
+impl Primitive {
+    /// Returns `true` if both values are identical
+    ///
+    /// Unlike [`PartialEq`], floating point numbers are compared bit by bit,
+    /// so a `NaN` is identical to itself.
+    pub fn is_identical(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::F32(a), Self::F32(b)) => a.to_bits() == b.to_bits(),
+            (Self::F64(a), Self::F64(b)) => a.to_bits() == b.to_bits(),
+            (a, b) => a == b,
+        }
+    }
+}
+
+/// Single modification applied by a [`Garbler`](crate::Garbler) to a leaf
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mutation {
+    /// Index of the leaf, in the order the garbler saw them
+    pub index: usize,
+    /// Value before garbling
+    pub original: Primitive,
+    /// Value after garbling
+    pub garbled: Primitive,
+}
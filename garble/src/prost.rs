@@ -0,0 +1,54 @@
+use crate::{formats::protobuf, Error, Garbler};
+use ::prost::Message;
+use alloc::vec::Vec;
+
+/// Garble the fields of a protobuf message
+///
+/// The message is encoded, its field values are garbled with
+/// [`protobuf::garble_fields`], and the result is decoded back into an `M`.
+/// This does not require the message to implement [`Garble`](crate::Garble),
+/// which is convenient for generated types. Unlike [`garble_prost_wire`], the
+/// encoding stays valid, so the garbled message usually decodes.
+///
+/// Returns an [`Error::Prost`] if the garbled message cannot be decoded, such
+/// as a string garbled into invalid UTF-8 because it looked like a nested
+/// message, or packed repeated values garbled as text because they looked like
+/// a string.
+///
+/// ```rust
+/// use garble::{garble_prost, SimpleGarbler};
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// struct Config {
+///     #[prost(string, tag = "1")]
+///     name: String,
+///     #[prost(uint32, tag = "2")]
+///     retries: u32,
+/// }
+///
+/// let mut garbler = SimpleGarbler::new(0.5).unwrap();
+/// let config = Config { name: "garble".to_string(), retries: 3 };
+/// dbg!(garble_prost(&config, &mut garbler).unwrap());
+/// ```
+pub fn garble_prost<M, G>(message: &M, garbler: &mut G) -> Result<M, Error>
+where
+    M: Message + Default,
+    G: Garbler,
+{
+    let bytes = protobuf::garble_fields(message.encode_to_vec(), garbler);
+    M::decode(bytes.as_slice()).map_err(Error::Prost)
+}
+
+/// Encode a protobuf message with wire-level faults
+///
+/// The message is encoded and garbled with [`protobuf::garble_wire`], which
+/// flips field numbers, changes wire types, corrupts varints, drops or
+/// duplicates fields and adds unknown fields. The result is meant to be passed
+/// to the decoding code under test.
+pub fn garble_prost_wire<M, G>(message: &M, garbler: &mut G) -> Vec<u8>
+where
+    M: Message,
+    G: Garbler,
+{
+    protobuf::garble_wire(message.encode_to_vec(), garbler)
+}
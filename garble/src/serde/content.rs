@@ -0,0 +1,711 @@
+use crate::{Error, Garble, Garbler};
+use ::serde::{
+    de::{self, EnumAccess, IntoDeserializer, VariantAccess, Visitor},
+    forward_to_deserialize_any,
+    ser::{self, Serialize},
+};
+use alloc::{borrow::ToOwned, boxed::Box, string::String, vec, vec::Vec};
+use core::fmt;
+
+/// Named fields of a struct or struct variant
+type Fields = Vec<(&'static str, Content)>;
+
+/// Value in the serde data model
+///
+/// This is an in-memory representation of everything a [`Serialize`]
+/// implementation can produce. Field names, variant names and map keys are
+/// kept as-is, so garbling a [`Content`] only changes its primitive leaves.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Content {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+    NewtypeStruct(&'static str, Box<Content>),
+    NewtypeVariant(&'static str, u32, &'static str, Box<Content>),
+    Seq(Vec<Content>),
+    Tuple(Vec<Content>),
+    TupleStruct(&'static str, Vec<Content>),
+    TupleVariant(&'static str, u32, &'static str, Vec<Content>),
+    Map(Vec<(Content, Content)>),
+    Struct(&'static str, Fields),
+    StructVariant(&'static str, u32, &'static str, Fields),
+}
+
+impl Content {
+    /// Serialize a value into a [`Content`]
+    pub(crate) fn serialize<T>(value: &T) -> Result<Self, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(ContentSerializer)
+    }
+
+    /// Deserialize a value from this [`Content`]
+    pub(crate) fn deserialize<T>(self) -> Result<T, Error>
+    where
+        T: de::DeserializeOwned,
+    {
+        T::deserialize(self)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Garble implementation
+
+fn garble_fields<G>(fields: Fields, garbler: &mut G) -> Fields
+where
+    G: Garbler,
+{
+    fields
+        .into_iter()
+        .map(|(key, value)| (key, value.garble(garbler)))
+        .collect()
+}
+
+impl Garble for Content {
+    type Output = Self;
+
+    fn garble<G>(self, garbler: &mut G) -> Self::Output
+    where
+        G: Garbler,
+    {
+        match self {
+            Self::Bool(v) => Self::Bool(garbler.garble_bool(v)),
+            Self::U8(v) => Self::U8(garbler.garble_u8(v)),
+            Self::U16(v) => Self::U16(garbler.garble_u16(v)),
+            Self::U32(v) => Self::U32(garbler.garble_u32(v)),
+            Self::U64(v) => Self::U64(garbler.garble_u64(v)),
+            Self::U128(v) => Self::U128(garbler.garble_u128(v)),
+            Self::I8(v) => Self::I8(garbler.garble_i8(v)),
+            Self::I16(v) => Self::I16(garbler.garble_i16(v)),
+            Self::I32(v) => Self::I32(garbler.garble_i32(v)),
+            Self::I64(v) => Self::I64(garbler.garble_i64(v)),
+            Self::I128(v) => Self::I128(garbler.garble_i128(v)),
+            Self::F32(v) => Self::F32(garbler.garble_f32(v)),
+            Self::F64(v) => Self::F64(garbler.garble_f64(v)),
+            Self::Char(v) => Self::Char(garbler.garble_char(v)),
+            Self::String(v) => Self::String(garbler.garble_str(v)),
+            Self::Bytes(v) => Self::Bytes(v.garble(garbler)),
+            Self::Some(v) => Self::Some(v.garble(garbler)),
+            Self::NewtypeStruct(name, v) => Self::NewtypeStruct(name, v.garble(garbler)),
+            Self::NewtypeVariant(name, index, variant, v) => {
+                Self::NewtypeVariant(name, index, variant, v.garble(garbler))
+            }
+            Self::Seq(v) => Self::Seq(v.garble(garbler)),
+            Self::Tuple(v) => Self::Tuple(v.garble(garbler)),
+            Self::TupleStruct(name, v) => Self::TupleStruct(name, v.garble(garbler)),
+            Self::TupleVariant(name, index, variant, v) => {
+                Self::TupleVariant(name, index, variant, v.garble(garbler))
+            }
+            // Map keys are part of the path to a value, so only values are
+            // garbled.
+            Self::Map(v) => Self::Map(
+                v.into_iter()
+                    .map(|(key, value)| (key, value.garble(garbler)))
+                    .collect(),
+            ),
+            Self::Struct(name, v) => Self::Struct(name, garble_fields(v, garbler)),
+            Self::StructVariant(name, index, variant, v) => {
+                Self::StructVariant(name, index, variant, garble_fields(v, garbler))
+            }
+            v @ (Self::None | Self::Unit | Self::UnitStruct(_) | Self::UnitVariant(..)) => v,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Serializer
+
+impl ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self::Serde(alloc::format!("{msg}"))
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self::Serde(alloc::format!("{msg}"))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ::serde::de::StdError for Error {}
+
+/// Serializer producing a [`Content`]
+struct ContentSerializer;
+
+impl ser::Serializer for ContentSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    type SerializeSeq = SerializeSeq;
+    type SerializeTuple = SerializeSeq;
+    type SerializeTupleStruct = SerializeSeq;
+    type SerializeTupleVariant = SerializeSeq;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeStruct;
+    type SerializeStructVariant = SerializeStruct;
+
+    // Compact representations are made of primitives that can be garbled
+    // without breaking deserialization, such as IP addresses as bytes rather
+    // than strings.
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Content, Error> {
+        Ok(Content::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Content, Error> {
+        Ok(Content::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Content, Error> {
+        Ok(Content::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Content, Error> {
+        Ok(Content::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Content, Error> {
+        Ok(Content::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Content, Error> {
+        Ok(Content::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Content, Error> {
+        Ok(Content::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Content, Error> {
+        Ok(Content::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Content, Error> {
+        Ok(Content::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Content, Error> {
+        Ok(Content::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Content, Error> {
+        Ok(Content::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Content, Error> {
+        Ok(Content::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Content, Error> {
+        Ok(Content::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Content, Error> {
+        Ok(Content::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Content, Error> {
+        Ok(Content::String(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Content, Error> {
+        Ok(Content::Bytes(v.to_owned()))
+    }
+
+    fn serialize_none(self) -> Result<Content, Error> {
+        Ok(Content::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Content, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(Content::Some(Box::new(Content::serialize(value)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Content, Error> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Content, Error> {
+        Ok(Content::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Content, Error> {
+        Ok(Content::UnitVariant(name, index, variant))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Content, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(Content::NewtypeStruct(
+            name,
+            Box::new(Content::serialize(value)?),
+        ))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Content, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(Content::NewtypeVariant(
+            name,
+            index,
+            variant,
+            Box::new(Content::serialize(value)?),
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeSeq, Error> {
+        Ok(SerializeSeq::new(Content::Seq, len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeSeq, Error> {
+        Ok(SerializeSeq::new(Content::Tuple, Some(len)))
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<SerializeSeq, Error> {
+        Ok(SerializeSeq::new(
+            move |items| Content::TupleStruct(name, items),
+            Some(len),
+        ))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeSeq, Error> {
+        Ok(SerializeSeq::new(
+            move |items| Content::TupleVariant(name, index, variant, items),
+            Some(len),
+        ))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap {
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<SerializeStruct, Error> {
+        Ok(SerializeStruct {
+            build: Box::new(move |fields| Content::Struct(name, fields)),
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeStruct, Error> {
+        Ok(SerializeStruct {
+            build: Box::new(move |fields| Content::StructVariant(name, index, variant, fields)),
+            fields: Vec::with_capacity(len),
+        })
+    }
+}
+
+/// Serializer for sequences, tuples and tuple structs or variants
+struct SerializeSeq {
+    build: Box<dyn FnOnce(Vec<Content>) -> Content>,
+    items: Vec<Content>,
+}
+
+impl SerializeSeq {
+    fn new<F>(build: F, len: Option<usize>) -> Self
+    where
+        F: FnOnce(Vec<Content>) -> Content + 'static,
+    {
+        Self {
+            build: Box::new(build),
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.items.push(Content::serialize(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Content, Error> {
+        Ok((self.build)(self.items))
+    }
+}
+
+macro_rules! impl_serialize_seq {
+    ($($trait:ident => $func:ident),*) => {
+        $(
+            impl ser::$trait for SerializeSeq {
+                type Ok = Content;
+                type Error = Error;
+
+                fn $func<T>(&mut self, value: &T) -> Result<(), Error>
+                where
+                    T: Serialize + ?Sized,
+                {
+                    self.push(value)
+                }
+
+                fn end(self) -> Result<Content, Error> {
+                    self.finish()
+                }
+            }
+        )*
+    };
+}
+impl_serialize_seq! {
+    SerializeSeq => serialize_element,
+    SerializeTuple => serialize_element,
+    SerializeTupleStruct => serialize_field,
+    SerializeTupleVariant => serialize_field
+}
+
+/// Serializer for maps
+struct SerializeMap {
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(Content::serialize(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| <Error as ser::Error>::custom("value serialized before its key"))?;
+        self.entries.push((key, Content::serialize(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, Error> {
+        Ok(Content::Map(self.entries))
+    }
+}
+
+/// Serializer for structs and struct variants
+struct SerializeStruct {
+    build: Box<dyn FnOnce(Fields) -> Content>,
+    fields: Fields,
+}
+
+impl SerializeStruct {
+    fn push<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.fields.push((key, Content::serialize(value)?));
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for SerializeStruct {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Content, Error> {
+        Ok((self.build)(self.fields))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeStruct {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Content, Error> {
+        Ok((self.build)(self.fields))
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Deserializer
+
+impl<'de> IntoDeserializer<'de, Error> for Content {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn visit_seq<'de, V>(items: Vec<Content>, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let mut seq = de::value::SeqDeserializer::new(items.into_iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+fn visit_map<'de, K, V>(entries: Vec<(K, Content)>, visitor: V) -> Result<V::Value, Error>
+where
+    K: IntoDeserializer<'de, Error>,
+    V: Visitor<'de>,
+{
+    let mut map = de::value::MapDeserializer::new(entries.into_iter());
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+impl<'de> de::Deserializer<'de> for Content {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::Bool(v) => visitor.visit_bool(v),
+            Self::U8(v) => visitor.visit_u8(v),
+            Self::U16(v) => visitor.visit_u16(v),
+            Self::U32(v) => visitor.visit_u32(v),
+            Self::U64(v) => visitor.visit_u64(v),
+            Self::U128(v) => visitor.visit_u128(v),
+            Self::I8(v) => visitor.visit_i8(v),
+            Self::I16(v) => visitor.visit_i16(v),
+            Self::I32(v) => visitor.visit_i32(v),
+            Self::I64(v) => visitor.visit_i64(v),
+            Self::I128(v) => visitor.visit_i128(v),
+            Self::F32(v) => visitor.visit_f32(v),
+            Self::F64(v) => visitor.visit_f64(v),
+            Self::Char(v) => visitor.visit_char(v),
+            Self::String(v) => visitor.visit_string(v),
+            Self::Bytes(v) => visitor.visit_byte_buf(v),
+            Self::None => visitor.visit_none(),
+            Self::Some(v) => visitor.visit_some(*v),
+            Self::Unit | Self::UnitStruct(_) => visitor.visit_unit(),
+            Self::NewtypeStruct(_, v) => visitor.visit_newtype_struct(*v),
+            Self::Seq(v) | Self::Tuple(v) | Self::TupleStruct(_, v) => visit_seq(v, visitor),
+            Self::Map(v) => visit_map(v, visitor),
+            Self::Struct(_, v) => visit_map(v, visitor),
+            // Without type information, enums use the externally tagged
+            // representation, such as `{"variant": value}`.
+            Self::UnitVariant(_, _, variant) => visitor.visit_str(variant),
+            Self::NewtypeVariant(_, _, variant, v) => visit_map(vec![(variant, *v)], visitor),
+            Self::TupleVariant(_, _, variant, v) => {
+                visit_map(vec![(variant, Self::Seq(v))], visitor)
+            }
+            Self::StructVariant(name, _, variant, v) => {
+                visit_map(vec![(variant, Self::Struct(name, v))], visitor)
+            }
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::None | Self::Unit => visitor.visit_none(),
+            Self::Some(v) => visitor.visit_some(*v),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::NewtypeStruct(_, v) => visitor.visit_newtype_struct(*v),
+            v => visitor.visit_newtype_struct(v),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Self::UnitVariant(_, _, variant) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: None,
+            }),
+            Self::NewtypeVariant(_, _, variant, v) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: Some(*v),
+            }),
+            Self::TupleVariant(_, _, variant, v) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: Some(Self::Seq(v)),
+            }),
+            Self::StructVariant(name, _, variant, v) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: Some(Self::Struct(name, v)),
+            }),
+            Self::String(v) => visitor.visit_enum(v.into_deserializer()),
+            v => v.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Deserializer for enum variants
+struct EnumDeserializer {
+    variant: &'static str,
+    value: Option<Content>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None => Ok(()),
+            Some(v) => de::Deserialize::deserialize(v),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.value {
+            Some(v) => seed.deserialize(v),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(v) => de::Deserializer::deserialize_any(v, visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(v) => de::Deserializer::deserialize_any(v, visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
@@ -0,0 +1,471 @@
+use crate::{Garble, Garbler};
+use ::serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use paste::paste;
+
+/// [`Deserializer`] that garbles primitive values produced by an inner
+/// deserializer
+///
+/// This simulates corrupted inbound messages for any type that implements
+/// [`Deserialize`](::serde::Deserialize), without requiring it to implement
+/// [`Garble`]. Struct fields, enum variants and map keys are passed through
+/// untouched.
+///
+/// Values are garbled as the type produced by the inner deserializer, which
+/// may be wider than the type being deserialized. For example, JSON integers
+/// are visited as `u64` or `i64`, so a garbled value may not fit a `u32` field
+/// and deserializing fails, just like it would with a corrupted message.
+///
+/// The tags of internally and adjacently tagged enums are deserialized as
+/// identifiers, even when serde buffers them, so they are also passed through
+/// untouched and only the content of the enum is garbled.
+///
+/// ```rust
+/// use garble::{GarblingDeserializer, SimpleGarbler};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Config {
+///     name: String,
+///     retries: u32,
+/// }
+///
+/// let mut garbler = SimpleGarbler::new(0.5).unwrap();
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"name":"garble","retries":3}"#);
+/// let config = Config::deserialize(GarblingDeserializer::new(&mut deserializer, &mut garbler));
+/// dbg!(config);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[derive(Debug)]
+pub struct GarblingDeserializer<'a, D, G> {
+    inner: D,
+    garbler: &'a mut G,
+}
+
+impl<'a, 'de, D, G> GarblingDeserializer<'a, D, G>
+where
+    D: Deserializer<'de>,
+    G: Garbler,
+{
+    /// Create a new [`GarblingDeserializer`] around the inner deserializer
+    pub fn new(inner: D, garbler: &'a mut G) -> Self {
+        Self { inner, garbler }
+    }
+}
+
+macro_rules! impl_deserialize {
+    ($($t:ident),*) => {
+        $(
+            paste! {
+                fn [<deserialize_ $t>]<V>(self, visitor: V) -> Result<V::Value, D::Error>
+                where
+                    V: Visitor<'de>,
+                {
+                    self.inner
+                        .[<deserialize_ $t>](GarblingVisitor::new(visitor, self.garbler))
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, D, G> Deserializer<'de> for GarblingDeserializer<'_, D, G>
+where
+    D: Deserializer<'de>,
+    G: Garbler,
+{
+    type Error = D::Error;
+
+    impl_deserialize! {
+        any, bool, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64, char, str, string,
+        bytes, byte_buf, option, unit, seq, map
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .deserialize_unit_struct(name, GarblingVisitor::new(visitor, self.garbler))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .deserialize_newtype_struct(name, GarblingVisitor::new(visitor, self.garbler))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .deserialize_tuple(len, GarblingVisitor::new(visitor, self.garbler))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .deserialize_tuple_struct(name, len, GarblingVisitor::new(visitor, self.garbler))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .deserialize_struct(name, fields, GarblingVisitor::new(visitor, self.garbler))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .deserialize_enum(name, variants, GarblingVisitor::new(visitor, self.garbler))
+    }
+
+    // Identifiers are struct fields or enum variants, so they are not garbled.
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_identifier(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_ignored_any(visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// [`Visitor`] that garbles primitive values before passing them to an inner
+/// visitor
+struct GarblingVisitor<'a, V, G> {
+    inner: V,
+    garbler: &'a mut G,
+}
+
+impl<'a, V, G> GarblingVisitor<'a, V, G> {
+    fn new(inner: V, garbler: &'a mut G) -> Self {
+        Self { inner, garbler }
+    }
+}
+
+macro_rules! impl_visit {
+    ($($t:ty),*) => {
+        $(
+            paste! {
+                fn [<visit_ $t>]<E>(self, v: $t) -> Result<V::Value, E>
+                where
+                    E: de::Error,
+                {
+                    self.inner.[<visit_ $t>](self.garbler.[<garble_ $t>](v))
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, V, G> Visitor<'de> for GarblingVisitor<'_, V, G>
+where
+    V: Visitor<'de>,
+    G: Garbler,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    impl_visit! { bool, char, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64 }
+
+    fn visit_str<E>(self, v: &str) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_str(&self.garbler.garble_str(v))
+    }
+
+    // Garbled values cannot borrow from the input, so they are passed as owned
+    // values unless they were left untouched.
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        let garbled = self.garbler.garble_str(v);
+        if garbled == v {
+            self.inner.visit_borrowed_str(v)
+        } else {
+            self.inner.visit_string(garbled)
+        }
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_string(self.garbler.garble_str(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        let garbled: Vec<u8> = v.garble(self.garbler);
+        self.inner.visit_bytes(&garbled)
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        let garbled: Vec<u8> = v.garble(self.garbler);
+        if garbled == v {
+            self.inner.visit_borrowed_bytes(v)
+        } else {
+            self.inner.visit_byte_buf(garbled)
+        }
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_byte_buf(v.garble(self.garbler))
+    }
+
+    fn visit_none<E>(self) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner
+            .visit_some(GarblingDeserializer::new(deserializer, self.garbler))
+    }
+
+    fn visit_unit<E>(self) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_unit()
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner
+            .visit_newtype_struct(GarblingDeserializer::new(deserializer, self.garbler))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<V::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.inner.visit_seq(GarblingAccess::new(seq, self.garbler))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.inner.visit_map(GarblingAccess::new(map, self.garbler))
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<V::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.inner
+            .visit_enum(GarblingAccess::new(data, self.garbler))
+    }
+}
+
+/// [`DeserializeSeed`] that garbles the deserialized value
+struct GarblingSeed<'a, T, G> {
+    inner: T,
+    garbler: &'a mut G,
+}
+
+impl<'a, T, G> GarblingSeed<'a, T, G> {
+    fn new(inner: T, garbler: &'a mut G) -> Self {
+        Self { inner, garbler }
+    }
+}
+
+impl<'de, T, G> DeserializeSeed<'de> for GarblingSeed<'_, T, G>
+where
+    T: DeserializeSeed<'de>,
+    G: Garbler,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner
+            .deserialize(GarblingDeserializer::new(deserializer, self.garbler))
+    }
+}
+
+/// Access to the elements of a sequence, map or enum that garbles values
+struct GarblingAccess<'a, A, G> {
+    inner: A,
+    garbler: &'a mut G,
+}
+
+impl<'a, A, G> GarblingAccess<'a, A, G> {
+    fn new(inner: A, garbler: &'a mut G) -> Self {
+        Self { inner, garbler }
+    }
+}
+
+impl<'de, A, G> SeqAccess<'de> for GarblingAccess<'_, A, G>
+where
+    A: SeqAccess<'de>,
+    G: Garbler,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner
+            .next_element_seed(GarblingSeed::new(seed, &mut *self.garbler))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A, G> MapAccess<'de> for GarblingAccess<'_, A, G>
+where
+    A: MapAccess<'de>,
+    G: Garbler,
+{
+    type Error = A::Error;
+
+    // Map keys are part of the path to a value, so they are not garbled.
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.inner.next_key_seed(seed)
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner
+            .next_value_seed(GarblingSeed::new(seed, &mut *self.garbler))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, 'de, A, G> EnumAccess<'de> for GarblingAccess<'a, A, G>
+where
+    A: EnumAccess<'de>,
+    G: Garbler,
+{
+    type Error = A::Error;
+    type Variant = GarblingAccess<'a, A::Variant, G>;
+
+    // Variant names are not garbled, only their content.
+    fn variant_seed<T>(self, seed: T) -> Result<(T::Value, Self::Variant), A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let (value, variant) = self.inner.variant_seed(seed)?;
+        Ok((value, GarblingAccess::new(variant, self.garbler)))
+    }
+}
+
+impl<'de, A, G> VariantAccess<'de> for GarblingAccess<'_, A, G>
+where
+    A: VariantAccess<'de>,
+    G: Garbler,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner
+            .newtype_variant_seed(GarblingSeed::new(seed, &mut *self.garbler))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .tuple_variant(len, GarblingVisitor::new(visitor, self.garbler))
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner
+            .struct_variant(fields, GarblingVisitor::new(visitor, self.garbler))
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{Error, Garble, Garbler};
+use ::serde::{de::DeserializeOwned, Serialize};
+
+mod content;
+use content::Content;
+
+mod de;
+pub use de::GarblingDeserializer;
+
+mod ser;
+pub use ser::{GarblingCompound, GarblingSerializer};
+
+/// Garble any value that can be serialized and deserialized with serde
+///
+/// The value is serialized into an in-memory representation of the serde data
+/// model, without going through a text or binary format. Every primitive leaf
+/// is then garbled with the [`Garbler`], before deserializing the result back
+/// into a `T`. Struct fields, enum variants and map keys are left untouched,
+/// so each garbled leaf keeps the same path as in the original value.
+///
+/// This uses the compact representation of types that support one, as
+/// garbling the bytes of an IP address is more likely to produce a valid value
+/// than garbling its textual form.
+///
+/// Returns an [`Error::Serde`] if the value cannot be serialized, or if the
+/// garbled value cannot be deserialized, such as a string garbled into an
+/// unknown enum variant.
+///
+/// ```rust
+/// use garble::{garble_serde, SimpleGarbler};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// struct Config {
+///     name: String,
+///     retries: u32,
+/// }
+///
+/// let mut garbler = SimpleGarbler::new(0.5).unwrap();
+/// let config = Config { name: "garble".to_string(), retries: 3 };
+/// dbg!(garble_serde(&config, &mut garbler).unwrap());
+/// ```
+pub fn garble_serde<T, G>(value: &T, garbler: &mut G) -> Result<T, Error>
+where
+    T: Serialize + DeserializeOwned,
+    G: Garbler,
+{
+    Content::serialize(value)?.garble(garbler).deserialize()
+}
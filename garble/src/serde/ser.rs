@@ -0,0 +1,366 @@
+use crate::{Garble, Garbler};
+use ::serde::ser::{self, Serialize, Serializer};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use paste::paste;
+
+/// [`Serializer`] that garbles primitive values before passing them to an
+/// inner serializer
+///
+/// This corrupts the output of existing serialization code without requiring
+/// the serialized types to implement [`Garble`]. Struct fields, enum variants
+/// and map keys are passed through untouched.
+///
+/// ```rust
+/// use garble::{GarblingSerializer, SimpleGarbler};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     name: String,
+///     retries: u32,
+/// }
+///
+/// let mut garbler = SimpleGarbler::new(0.5).unwrap();
+/// let config = Config { name: "garble".to_string(), retries: 3 };
+///
+/// let mut output = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut output);
+/// config
+///     .serialize(GarblingSerializer::new(&mut serializer, &mut garbler))
+///     .unwrap();
+/// dbg!(String::from_utf8_lossy(&output));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[derive(Debug)]
+pub struct GarblingSerializer<'a, S, G> {
+    inner: S,
+    garbler: &'a mut G,
+}
+
+impl<'a, S, G> GarblingSerializer<'a, S, G>
+where
+    S: Serializer,
+    G: Garbler,
+{
+    /// Create a new [`GarblingSerializer`] around the inner serializer
+    pub fn new(inner: S, garbler: &'a mut G) -> Self {
+        Self { inner, garbler }
+    }
+}
+
+/// Value that is garbled when serialized
+///
+/// [`Serialize::serialize`] only takes a shared reference, so the garbler is
+/// borrowed mutably for the duration of the serialization.
+struct Garbled<'a, 'b, T: ?Sized, G> {
+    value: &'a T,
+    garbler: RefCell<&'b mut G>,
+}
+
+impl<'a, 'b, T, G> Garbled<'a, 'b, T, G>
+where
+    T: ?Sized,
+{
+    fn new(value: &'a T, garbler: &'b mut G) -> Self {
+        Self {
+            value,
+            garbler: RefCell::new(garbler),
+        }
+    }
+}
+
+impl<T, G> Serialize for Garbled<'_, '_, T, G>
+where
+    T: Serialize + ?Sized,
+    G: Garbler,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut garbler = self.garbler.borrow_mut();
+        self.value
+            .serialize(GarblingSerializer::new(serializer, &mut **garbler))
+    }
+}
+
+macro_rules! impl_func {
+    ($($t:ty),*) => {
+        $(
+            paste! {
+                fn [<serialize_ $t>](self, v: $t) -> Result<S::Ok, S::Error> {
+                    self.inner.[<serialize_ $t>](self.garbler.[<garble_ $t>](v))
+                }
+            }
+        )*
+    };
+}
+
+impl<'a, S, G> Serializer for GarblingSerializer<'a, S, G>
+where
+    S: Serializer,
+    G: Garbler,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    type SerializeSeq = GarblingCompound<'a, S::SerializeSeq, G>;
+    type SerializeTuple = GarblingCompound<'a, S::SerializeTuple, G>;
+    type SerializeTupleStruct = GarblingCompound<'a, S::SerializeTupleStruct, G>;
+    type SerializeTupleVariant = GarblingCompound<'a, S::SerializeTupleVariant, G>;
+    type SerializeMap = GarblingCompound<'a, S::SerializeMap, G>;
+    type SerializeStruct = GarblingCompound<'a, S::SerializeStruct, G>;
+    type SerializeStructVariant = GarblingCompound<'a, S::SerializeStructVariant, G>;
+
+    impl_func! { bool, char, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64 }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(&self.garbler.garble_str(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        let bytes: Vec<u8> = v.garble(self.garbler);
+        self.inner.serialize_bytes(&bytes)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.inner
+            .serialize_some(&Garbled::new(value, self.garbler))
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_variant(name, index, variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.inner
+            .serialize_newtype_struct(name, &Garbled::new(value, self.garbler))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.inner.serialize_newtype_variant(
+            name,
+            index,
+            variant,
+            &Garbled::new(value, self.garbler),
+        )
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        Ok(GarblingCompound::new(
+            self.inner.serialize_seq(len)?,
+            self.garbler,
+        ))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        Ok(GarblingCompound::new(
+            self.inner.serialize_tuple(len)?,
+            self.garbler,
+        ))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        Ok(GarblingCompound::new(
+            self.inner.serialize_tuple_struct(name, len)?,
+            self.garbler,
+        ))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        Ok(GarblingCompound::new(
+            self.inner
+                .serialize_tuple_variant(name, index, variant, len)?,
+            self.garbler,
+        ))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        Ok(GarblingCompound::new(
+            self.inner.serialize_map(len)?,
+            self.garbler,
+        ))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        Ok(GarblingCompound::new(
+            self.inner.serialize_struct(name, len)?,
+            self.garbler,
+        ))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        Ok(GarblingCompound::new(
+            self.inner
+                .serialize_struct_variant(name, index, variant, len)?,
+            self.garbler,
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Serializer for compound values of a [`GarblingSerializer`]
+///
+/// This garbles the elements, fields or map values of a compound value before
+/// passing them to the inner serializer.
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[derive(Debug)]
+pub struct GarblingCompound<'a, C, G> {
+    inner: C,
+    garbler: &'a mut G,
+}
+
+impl<'a, C, G> GarblingCompound<'a, C, G> {
+    fn new(inner: C, garbler: &'a mut G) -> Self {
+        Self { inner, garbler }
+    }
+}
+
+macro_rules! impl_compound {
+    ($($trait:ident => $func:ident),*) => {
+        $(
+            impl<C, G> ser::$trait for GarblingCompound<'_, C, G>
+            where
+                C: ser::$trait,
+                G: Garbler,
+            {
+                type Ok = C::Ok;
+                type Error = C::Error;
+
+                fn $func<T>(&mut self, value: &T) -> Result<(), C::Error>
+                where
+                    T: Serialize + ?Sized,
+                {
+                    self.inner.$func(&Garbled::new(value, &mut *self.garbler))
+                }
+
+                fn end(self) -> Result<C::Ok, C::Error> {
+                    self.inner.end()
+                }
+            }
+        )*
+    };
+}
+impl_compound! {
+    SerializeSeq => serialize_element,
+    SerializeTuple => serialize_element,
+    SerializeTupleStruct => serialize_field,
+    SerializeTupleVariant => serialize_field
+}
+
+impl<C, G> ser::SerializeMap for GarblingCompound<'_, C, G>
+where
+    C: ser::SerializeMap,
+    G: Garbler,
+{
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    // Map keys are part of the path to a value, so they are not garbled.
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), C::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.inner.serialize_key(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), C::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.inner
+            .serialize_value(&Garbled::new(value, &mut *self.garbler))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+macro_rules! impl_compound_struct {
+    ($($trait:ident),*) => {
+        $(
+            impl<C, G> ser::$trait for GarblingCompound<'_, C, G>
+            where
+                C: ser::$trait,
+                G: Garbler,
+            {
+                type Ok = C::Ok;
+                type Error = C::Error;
+
+                fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), C::Error>
+                where
+                    T: Serialize + ?Sized,
+                {
+                    self.inner
+                        .serialize_field(key, &Garbled::new(value, &mut *self.garbler))
+                }
+
+                fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+                    self.inner.skip_field(key)
+                }
+
+                fn end(self) -> Result<C::Ok, C::Error> {
+                    self.inner.end()
+                }
+            }
+        )*
+    };
+}
+impl_compound_struct! { SerializeStruct, SerializeStructVariant }
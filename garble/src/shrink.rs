@@ -0,0 +1,167 @@
+use crate::{Garble, Garbler, Mutation, RecordingGarbler, ReplayGarbler};
+use alloc::vec::Vec;
+
+/// Minimal set of mutations that still makes a predicate fail
+///
+/// See [`Garbler::shrink`].
+#[derive(Debug)]
+pub struct Shrunk<T> {
+    /// Value garbled with only the minimal set of mutations
+    pub value: T,
+    /// Minimal set of mutations, sorted by leaf index
+    pub mutations: Vec<Mutation>,
+}
+
+/// Garble a value and shrink the applied mutations to a minimal failing set
+///
+/// The predicate returns `true` when the garbled value makes the test fail.
+/// If the garbled value does not fail, this returns `None`. Otherwise, the
+/// recorded mutations are reduced with delta debugging until removing any
+/// single mutation makes the predicate pass.
+///
+/// Structural faults, such as inverted ranges, are applied but not recorded.
+/// If replaying the recorded mutations does not make the predicate fail, the
+/// failure depends on structural faults: the garbled value and all recorded
+/// mutations are returned without shrinking.
+pub fn shrink<T, G, F>(value: T, garbler: G, mut predicate: F) -> Option<Shrunk<T::Output>>
+where
+    T: Garble + Clone,
+    G: Garbler,
+    F: FnMut(&T::Output) -> bool,
+{
+    let mut recorder = RecordingGarbler::new(garbler);
+    let garbled = value.clone().garble(&mut recorder);
+    if !predicate(&garbled) {
+        return None;
+    }
+
+    let mut test = |mutations: &[Mutation]| {
+        predicate(&value.clone().garble(&mut ReplayGarbler::new(mutations)))
+    };
+    let log = recorder.into_log();
+    if !test(&log) {
+        return Some(Shrunk {
+            value: garbled,
+            mutations: log,
+        });
+    }
+    let mutations = ddmin(log, &mut test);
+
+    Some(Shrunk {
+        value: value.garble(&mut ReplayGarbler::new(&mutations)),
+        mutations,
+    })
+}
+
+/// Delta debugging minimization
+///
+/// This assumes that `test` fails on the full set of mutations.
+fn ddmin<F>(mut mutations: Vec<Mutation>, test: &mut F) -> Vec<Mutation>
+where
+    F: FnMut(&[Mutation]) -> bool,
+{
+    if test(&[]) {
+        return Vec::new();
+    }
+
+    let mut n = 2;
+    while mutations.len() >= 2 {
+        let chunk_size = mutations.len().div_ceil(n);
+        let chunks = mutations
+            .chunks(chunk_size)
+            .map(<[Mutation]>::to_vec)
+            .collect::<Vec<_>>();
+
+        // Reduce to a subset
+        if let Some(chunk) = chunks.iter().find(|chunk| test(chunk)) {
+            mutations = chunk.clone();
+            n = 2;
+            continue;
+        }
+
+        // Reduce to a complement
+        let complement = (0..chunks.len())
+            .map(|i| {
+                chunks
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .flat_map(|(_, chunk)| chunk.iter().cloned())
+                    .collect::<Vec<_>>()
+            })
+            .find(|complement| test(complement));
+        if let Some(complement) = complement {
+            mutations = complement;
+            n = (n - 1).max(2);
+            continue;
+        }
+
+        // Increase granularity
+        if n >= mutations.len() {
+            break;
+        }
+        n = (n * 2).min(mutations.len());
+    }
+
+    mutations
+}
+
+#[cfg(all(test, feature = "std", feature = "simple"))]
+mod tests {
+    use super::*;
+    use crate::SimpleGarbler;
+
+    #[test]
+    fn test_shrink() {
+        // GIVEN a value where every leaf is garbled
+        let value = [true; 16];
+        // WHEN we shrink with a predicate failing on the 3rd and 11th leaves
+        let shrunk = SimpleGarbler::new(1.0)
+            .unwrap()
+            .shrink(value, |v| !v[3] && !v[11])
+            .unwrap();
+        // THEN only these two mutations should remain
+        let indices = shrunk.mutations.iter().map(|m| m.index).collect::<Vec<_>>();
+        assert_eq!(indices, vec![3, 11]);
+        let mut expected = [true; 16];
+        expected[3] = false;
+        expected[11] = false;
+        assert_eq!(shrunk.value, expected);
+    }
+
+    #[test]
+    fn test_shrink_passing() {
+        // GIVEN a garbler that never garbles
+        let garbler = SimpleGarbler::new(0.0).unwrap();
+        // WHEN we shrink with a predicate that never fails
+        let shrunk = garbler.shrink(1u32, |_| false);
+        // THEN there should be nothing to shrink
+        assert!(shrunk.is_none());
+    }
+
+    #[test]
+    fn test_shrink_structural() {
+        // GIVEN a garbler that only applies structural faults
+        let garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        // WHEN we shrink with a predicate failing on empty ranges
+        let shrunk = garbler.shrink(1u32..10, |v| v.is_empty()).unwrap();
+        // THEN the garbled value should still fail
+        assert!(shrunk.value.is_empty());
+        assert!(shrunk.mutations.is_empty());
+    }
+
+    #[test]
+    fn test_shrink_unrelated() {
+        // GIVEN a predicate that always fails
+        let garbler = SimpleGarbler::new(1.0).unwrap();
+        // WHEN we shrink
+        let shrunk = garbler.shrink((1u32, 2u32), |_| true).unwrap();
+        // THEN no mutation is needed to make it fail
+        assert!(shrunk.mutations.is_empty());
+        assert_eq!(shrunk.value, (1u32, 2u32));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{iter::Window, Garble, Garbler};
+use core::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// [`Stream`] that garbles items and simulates a lossy message stream
+    ///
+    /// This is the asynchronous version of a [`Garbled`](crate::Garbled)
+    /// iterator, created by [`GarbleStream::garbled`]. Every item is garbled
+    /// with [`Garble::garble`]. One of the following faults can also be
+    /// applied to each item, as picked by [`Garbler::structural_fault`]:
+    ///
+    /// * Dropped item
+    /// * Duplicated item
+    /// * Delayed item, yielded after a full window of items
+    /// * Reordered item, swapped with the next one
+    ///
+    /// Items held back are yielded once the inner stream ends, so no item is
+    /// lost unless it was dropped.
+    #[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+    #[derive(Debug)]
+    pub struct GarbledStream<S, G>
+    where
+        S: Stream,
+        S::Item: Garble,
+    {
+        #[pin]
+        inner: S,
+        garbler: G,
+        window: Window<<S::Item as Garble>::Output>,
+    }
+}
+
+impl<S, G> GarbledStream<S, G>
+where
+    S: Stream,
+    S::Item: Garble,
+    <S::Item as Garble>::Output: Clone,
+    G: Garbler,
+{
+    /// Create a new [`GarbledStream`] around the inner stream
+    pub fn new(inner: S, garbler: G) -> Self {
+        Self {
+            inner,
+            garbler,
+            window: Window::new(),
+        }
+    }
+
+    /// Set the size of the window for delayed items
+    ///
+    /// This is the number of items a delayed item is held back for, and the
+    /// maximum number of items held back at once. Defaults to 4.
+    pub fn window(mut self, size: usize) -> Self {
+        self.window.set_size(size);
+        self
+    }
+
+    /// Reference to the inner stream
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Mutable reference to the inner stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Pinned mutable reference to the inner stream
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().inner
+    }
+
+    /// Reference to the garbler
+    pub fn garbler(&self) -> &G {
+        &self.garbler
+    }
+
+    /// Consume the [`GarbledStream`], returning the inner stream
+    ///
+    /// Items held back are lost.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, G> Stream for GarbledStream<S, G>
+where
+    S: Stream,
+    S::Item: Garble,
+    <S::Item as Garble>::Output: Clone,
+    G: Garbler,
+{
+    type Item = <S::Item as Garble>::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            if let Some(item) = this.window.pop() {
+                return Poll::Ready(Some(item));
+            }
+            match ready!(this.inner.as_mut().poll_next(cx)) {
+                Some(item) => {
+                    let item = item.garble(&mut *this.garbler);
+                    this.window.push(item, this.garbler);
+                }
+                None => {
+                    this.window.flush();
+                    return Poll::Ready(this.window.pop());
+                }
+            }
+        }
+    }
+}
+
+/// Extension trait to garble the items of a [`Stream`]
+///
+/// ```rust
+/// use futures::{executor, StreamExt};
+/// use garble::{GarbleStream, SimpleGarbler};
+///
+/// let mut garbler = SimpleGarbler::builder()
+///     .rate(0.1)
+///     .structural_rate(0.2)
+///     .build()
+///     .unwrap();
+/// let stream = futures::stream::iter(0..10u32).garbled(&mut garbler);
+/// let messages = executor::block_on(stream.collect::<Vec<_>>());
+/// dbg!(messages);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub trait GarbleStream: Stream {
+    /// Garble each item, and drop, duplicate, delay or reorder items
+    ///
+    /// See [`GarbledStream`] for more details.
+    fn garbled<G>(self, garbler: G) -> GarbledStream<Self, G>
+    where
+        Self: Sized,
+        Self::Item: Garble,
+        <Self::Item as Garble>::Output: Clone,
+        G: Garbler,
+    {
+        GarbledStream::new(self, garbler)
+    }
+}
+
+impl<S> GarbleStream for S where S: Stream {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::CountingGarbler;
+    use futures::{executor, stream, StreamExt};
+
+    #[test]
+    fn test_passthrough() {
+        // GIVEN a garbler that does not change anything
+        let mut garbler = CountingGarbler::new();
+        // WHEN we garble a stream
+        let items = executor::block_on(
+            stream::iter(0..5u32)
+                .garbled(&mut garbler)
+                .collect::<Vec<_>>(),
+        );
+        // THEN the items should be unchanged
+        assert_eq!(items, vec![0, 1, 2, 3, 4]);
+        // AND every item should be garbled
+        assert_eq!(garbler.count(), 5);
+    }
+
+    #[cfg(feature = "simple")]
+    #[test]
+    fn test_faults() {
+        use crate::SimpleGarbler;
+
+        // GIVEN a garbler that always applies structural faults, but never
+        // garbles values
+        let garbler = SimpleGarbler::builder()
+            .rate(0.0)
+            .structural_rate(1.0)
+            .build()
+            .unwrap();
+        // WHEN we garble a stream
+        let input = (0..100u32).collect::<Vec<_>>();
+        let stream = stream::iter(input.clone()).garbled(garbler).window(2);
+        let output = executor::block_on(stream.collect::<Vec<_>>());
+        // THEN the items should differ
+        assert_ne!(output, input);
+        // AND every item should come from the input
+        assert!(output.iter().all(|item| input.contains(item)));
+    }
+}
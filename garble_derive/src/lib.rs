@@ -94,8 +94,6 @@ fn derive_garble(mut s: Structure) -> TokenStream {
     let name = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
     let mut where_clause = where_clause.cloned();
-    let dummy_const: syn::Ident =
-        syn::parse_str(&format!("_DERIVE_garble_Garble_g_FOR_{}", name)).unwrap();
     s.add_trait_bounds(
         &syn::parse_quote!(::garble::Garble<Output = T>),
         &mut where_clause,
@@ -103,8 +101,7 @@ fn derive_garble(mut s: Structure) -> TokenStream {
     );
 
     quote! {
-        #[allow(non_upper_case_globals)]
-        const #dummy_const: () = {
+        const _: () = {
             impl #impl_generics ::garble::Garble for #name #ty_generics #where_clause {
                 type Output = Self;
 
@@ -122,6 +119,8 @@ fn derive_garble(mut s: Structure) -> TokenStream {
 decl_derive!([Garble, attributes(nogarble)] => derive_garble);
 
 #[cfg(test)]
+// Expected expansions are compiled as written
+#[allow(clippy::init_numbered_fields, clippy::match_single_binding)]
 mod tests {
     use super::*;
 
@@ -135,8 +134,7 @@ mod tests {
                 }
             }
             expands to {
-                #[allow(non_upper_case_globals)]
-                const _DERIVE_garble_Garble_g_FOR_TestEnum : () = {
+                const _: () = {
                     impl ::garble::Garble for TestEnum {
                         type Output = Self;
                         fn garble<G> (self, garbler: &mut G) -> Self
@@ -170,8 +168,7 @@ mod tests {
                 }
             }
             expands to {
-                #[allow(non_upper_case_globals)]
-                const _DERIVE_garble_Garble_g_FOR_MyStruct : () = {
+                const _: () = {
                     impl ::garble::Garble for MyStruct {
                         type Output = Self;
                         fn garble<G>(self, garbler: & mut G)-> Self where G: ::garble::Garbler {
@@ -199,8 +196,7 @@ mod tests {
                 }
             }
             expands to {
-                #[allow(non_upper_case_globals)]
-                const _DERIVE_garble_Garble_g_FOR_MyStruct : () = {
+                const _: () = {
                     impl ::garble::Garble for MyStruct {
                         type Output = Self;
                         fn garble<G>(self, garbler: & mut G)-> Self where G: ::garble::Garbler {
@@ -228,8 +224,7 @@ mod tests {
                 }
             }
             expands to {
-                #[allow(non_upper_case_globals)]
-                const _DERIVE_garble_Garble_g_FOR_MyStruct : () = {
+                const _: () = {
                     impl<T> ::garble::Garble for MyStruct<T>
                     where
                         T: ::garble::Garble<Output = T>
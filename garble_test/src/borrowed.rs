@@ -0,0 +1,31 @@
+use crate::utils::ZeroGarbler;
+use garble::Garble;
+
+#[test]
+fn test_mut_ref() {
+    let mut value = (1u32, String::from("hello"));
+    (&mut value).garble(&mut ZeroGarbler);
+    assert_eq!(value, (0, String::new()));
+}
+
+#[test]
+fn test_mut_slice() {
+    let mut buffer = vec![1u8, 2, 3, 4];
+    (&mut buffer[1..3]).garble(&mut ZeroGarbler);
+    assert_eq!(buffer, vec![1, 0, 0, 4]);
+}
+
+#[test]
+fn test_slice() {
+    let buffer = String::from("hello, world").into_bytes();
+    let garbled = buffer[..5].garble(&mut ZeroGarbler);
+    assert_eq!(garbled, vec![0; 5]);
+    assert_eq!(buffer, b"hello, world");
+}
+
+#[test]
+fn test_borrowed_str() {
+    let input = String::from("hello, world");
+    let garbled = input.split(", ").collect::<Vec<_>>()[..].garble(&mut ZeroGarbler);
+    assert_eq!(garbled, vec![String::new(), String::new()]);
+}
@@ -0,0 +1,47 @@
+use crate::utils::ZeroGarbler;
+use garble::GarbleInPlace;
+use std::{cell, sync};
+
+#[test]
+fn test_cell() {
+    let value = cell::Cell::new(1u32);
+    value.garble_in_place(&mut ZeroGarbler);
+    assert_eq!(value.get(), 0);
+}
+
+#[test]
+fn test_refcell() {
+    let value = cell::RefCell::new(String::from("hello"));
+    value.garble_in_place(&mut ZeroGarbler);
+    assert_eq!(*value.borrow(), "");
+}
+
+#[test]
+fn test_refcell_borrowed() {
+    let value = cell::RefCell::new(String::from("hello"));
+    let borrowed = value.borrow();
+    value.garble_in_place(&mut ZeroGarbler);
+    assert_eq!(*borrowed, "hello");
+}
+
+#[test]
+fn test_mutex() {
+    let value = sync::Arc::new(sync::Mutex::new(vec![1u32, 2, 3]));
+    let shared = sync::Arc::clone(&value);
+    value.garble_in_place(&mut ZeroGarbler);
+    assert_eq!(*shared.lock().unwrap(), vec![0, 0, 0]);
+}
+
+#[test]
+fn test_rwlock() {
+    let value = sync::RwLock::new((1u32, 2u64));
+    value.garble_in_place(&mut ZeroGarbler);
+    assert_eq!(*value.read().unwrap(), (0, 0));
+}
+
+#[test]
+fn test_atomic() {
+    let value = sync::atomic::AtomicU32::new(1);
+    value.garble_in_place(&mut ZeroGarbler);
+    assert_eq!(value.load(sync::atomic::Ordering::SeqCst), 0);
+}
@@ -0,0 +1,34 @@
+use crate::utils::PrimitiveCountingGarbler;
+use garble::{CountingGarbler, Garble, Garbler};
+use std::fmt::Debug;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Assert that a custom garbler sees as many leaves as a [`CountingGarbler`],
+/// and that each single fault reaches exactly one of its primitives
+fn assert_leaves<T>(value: T, expected: usize)
+where
+    T: Garble<Output = T> + Clone + Debug + PartialEq,
+{
+    // GIVEN a garbler that only implements the required functions
+    let mut garbler = PrimitiveCountingGarbler::default();
+    // WHEN we garble the value
+    let garbled = value.clone().garble(&mut garbler);
+    // THEN the value should be unchanged
+    assert_eq!(garbled, value);
+    // AND it should see as many leaves as a CountingGarbler
+    assert_eq!(CountingGarbler::count_leaves(&value), expected);
+    assert_eq!(garbler.count, expected);
+
+    // WHEN we enumerate single faults with the same garbler
+    let mut garbler = PrimitiveCountingGarbler::default();
+    let faults = (&mut garbler).single_faults(value).count();
+    // THEN each fault should reach exactly one primitive
+    assert_eq!(faults, expected);
+    assert_eq!(garbler.count, expected);
+}
+
+#[test]
+fn test_time_leaves() {
+    assert_leaves(Duration::from_millis(1_500), 1);
+    assert_leaves((Duration::ZERO, SystemTime::now(), Instant::now(), 1u32), 4);
+}
@@ -2,5 +2,10 @@
 
 mod utils;
 
+mod borrowed;
+mod in_place;
+mod leaves;
 mod nogarble;
+mod prost;
+mod serde;
 mod zero;
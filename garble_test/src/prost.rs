@@ -0,0 +1,124 @@
+use crate::utils::ZeroGarbler;
+use garble::{garble_prost, garble_prost_wire, CountingGarbler, SimpleGarbler};
+use prost::Message;
+
+#[derive(Clone, PartialEq, Message)]
+struct Inner {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(uint32, tag = "2")]
+    count: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Outer {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(message, optional, tag = "2")]
+    inner: Option<Inner>,
+    #[prost(bytes = "vec", tag = "3")]
+    data: Vec<u8>,
+    #[prost(double, tag = "4")]
+    ratio: f64,
+    #[prost(string, repeated, tag = "5")]
+    tags: Vec<String>,
+    #[prost(bool, tag = "6")]
+    flag: bool,
+    #[prost(sint32, tag = "7")]
+    offset: i32,
+    #[prost(uint32, repeated, tag = "8")]
+    ids: Vec<u32>,
+}
+
+fn outer() -> Outer {
+    Outer {
+        id: 42,
+        inner: Some(Inner {
+            name: String::from("hello"),
+            count: 3,
+        }),
+        data: vec![0xff, 0x00],
+        ratio: 1.5,
+        tags: vec![String::from("a"), String::from("b")],
+        flag: true,
+        offset: -7,
+        ids: vec![1, 2, 300],
+    }
+}
+
+#[test]
+fn test_prost_passthrough() {
+    // GIVEN a message with nested messages, bytes and repeated fields
+    let message = outer();
+    // WHEN we garble it with a garbler that does not change anything
+    let mut garbler = CountingGarbler::new();
+    let garbled = garble_prost(&message, &mut garbler).unwrap();
+    // THEN the message should be the same as the original
+    assert_eq!(garbled, message);
+    // AND every field value should be garbled
+    //   id, name, count, 2 bytes, ratio, 2 tags, flag, offset, 3 ids
+    assert_eq!(garbler.count(), 13);
+}
+
+#[test]
+fn test_prost_zero() {
+    // GIVEN a message
+    let message = outer();
+    // WHEN we garble it with a ZeroGarbler
+    let garbled = garble_prost(&message, &mut ZeroGarbler).unwrap();
+    // THEN the fields should be zeroed, while nested messages are kept
+    assert_eq!(
+        garbled,
+        Outer {
+            id: 0,
+            inner: Some(Inner {
+                name: String::new(),
+                count: 0,
+            }),
+            data: vec![0, 0],
+            ratio: 0.0,
+            tags: vec![String::new(), String::new()],
+            flag: false,
+            offset: 0,
+            ids: vec![0, 0, 0],
+        }
+    );
+}
+
+#[test]
+fn test_prost_packed() {
+    // GIVEN a message with packed repeated varints
+    let message = outer();
+    let mut garbler = SimpleGarbler::new(1.0).unwrap();
+    for _ in 0..32 {
+        // WHEN we garble it
+        let garbled = garble_prost(&message, &mut garbler);
+        // THEN it should still decode
+        assert!(garbled.is_ok(), "{garbled:?}");
+    }
+}
+
+#[test]
+fn test_prost_wire_passthrough() {
+    // GIVEN a message
+    let message = outer();
+    // WHEN we garble it with a garbler that never applies structural faults
+    let bytes = garble_prost_wire(&message, &mut CountingGarbler::new());
+    // THEN the encoding should be unchanged
+    assert_eq!(bytes, message.encode_to_vec());
+}
+
+#[test]
+fn test_prost_wire_faults() {
+    // GIVEN a message
+    let message = outer();
+    // WHEN we garble it with a garbler that always applies structural faults
+    let mut garbler = SimpleGarbler::builder()
+        .rate(0.0)
+        .structural_rate(1.0)
+        .build()
+        .unwrap();
+    let bytes = garble_prost_wire(&message, &mut garbler);
+    // THEN the encoding should be different
+    assert_ne!(bytes, message.encode_to_vec());
+}
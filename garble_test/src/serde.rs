@@ -0,0 +1,233 @@
+use crate::utils::ZeroGarbler;
+use garble::{garble_serde, CountingGarbler, Error, GarblingDeserializer, GarblingSerializer};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, net::Ipv4Addr, num::NonZeroU32};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Point(i32, i32),
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum Event {
+    Click { x: u32 },
+    Key { code: u32 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Id(u64);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Item {
+    id: Id,
+    name: String,
+    tags: Vec<String>,
+    shapes: Vec<Shape>,
+    parent: Option<Box<Item>>,
+    labels: BTreeMap<String, u8>,
+    addr: Ipv4Addr,
+}
+
+fn item() -> Item {
+    Item {
+        id: Id(42),
+        name: String::from("hello"),
+        tags: vec![String::from("a"), String::from("b")],
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Point(-1, 1),
+            Shape::Rect {
+                width: 3,
+                height: 4,
+            },
+        ],
+        parent: Some(Box::new(Item {
+            id: Id(1),
+            name: String::from("parent"),
+            tags: Vec::new(),
+            shapes: Vec::new(),
+            parent: None,
+            labels: BTreeMap::new(),
+            addr: Ipv4Addr::LOCALHOST,
+        })),
+        labels: BTreeMap::from([(String::from("key"), 7)]),
+        addr: Ipv4Addr::new(10, 0, 0, 1),
+    }
+}
+
+#[test]
+fn test_serde_passthrough() {
+    // GIVEN a value with nested structs, enums, options and maps
+    let value = item();
+    // WHEN we garble it with a garbler that does not change anything
+    let garbled = garble_serde(&value, &mut CountingGarbler::new()).unwrap();
+    // THEN the value should be the same as the original
+    assert_eq!(garbled, value);
+}
+
+#[test]
+fn test_serde_leaves() {
+    // GIVEN a value
+    let value = item();
+    // WHEN we count the leaves seen by the garbler
+    let mut garbler = CountingGarbler::new();
+    garble_serde(&value, &mut garbler).unwrap();
+    // THEN every primitive leaf should be garbled, but not map keys
+    //   own leaves: id, name, 2 tags, circle, 2 point, 2 rect, label, 4 octets
+    //   parent leaves: id, name, 4 octets
+    assert_eq!(garbler.count(), 14 + 6);
+}
+
+#[test]
+fn test_serde_zero() {
+    // GIVEN a value
+    let value = item();
+    // WHEN we garble it with a ZeroGarbler
+    let garbled = garble_serde(&value, &mut ZeroGarbler).unwrap();
+    // THEN the leaves should be zeroed, while the structure and keys are kept
+    assert_eq!(garbled.id, Id(0));
+    assert_eq!(garbled.name, "");
+    assert_eq!(garbled.tags, vec![String::new(), String::new()]);
+    assert_eq!(
+        garbled.shapes,
+        vec![
+            Shape::Empty,
+            Shape::Circle(0.0),
+            Shape::Point(0, 0),
+            Shape::Rect {
+                width: 0,
+                height: 0
+            }
+        ]
+    );
+    assert_eq!(garbled.labels, BTreeMap::from([(String::from("key"), 0)]));
+    assert_eq!(garbled.addr, Ipv4Addr::UNSPECIFIED);
+    assert_eq!(garbled.parent.unwrap().id, Id(0));
+}
+
+#[test]
+fn test_serde_invalid() {
+    // GIVEN a value that cannot be zero
+    let value = NonZeroU32::new(5).unwrap();
+    // WHEN we garble it with a ZeroGarbler
+    let garbled = garble_serde(&value, &mut ZeroGarbler);
+    // THEN the garbled value should fail to deserialize
+    assert!(matches!(garbled, Err(Error::Serde(_))));
+}
+
+fn to_json<T, G>(value: &T, garbler: &mut G) -> String
+where
+    T: Serialize,
+    G: garble::Garbler,
+{
+    let mut output = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut output);
+    value
+        .serialize(GarblingSerializer::new(&mut serializer, garbler))
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_serializer_passthrough() {
+    // GIVEN a value
+    let value = item();
+    // WHEN we serialize it with a garbler that does not change anything
+    let mut garbler = CountingGarbler::new();
+    let output = to_json(&value, &mut garbler);
+    // THEN the output should be the same as without garbling
+    assert_eq!(output, serde_json::to_string(&value).unwrap());
+    // AND every primitive leaf should be garbled, but not map keys
+    //   the IP addresses are serialized as strings in JSON
+    assert_eq!(garbler.count(), 11 + 3);
+}
+
+#[test]
+fn test_serializer_zero() {
+    // GIVEN a value
+    let value = (1u32, "hello", Some(Shape::Circle(1.5)), vec![true]);
+    // WHEN we serialize it with a ZeroGarbler
+    let output = to_json(&value, &mut ZeroGarbler);
+    // THEN the leaves should be zeroed, while the structure is kept
+    assert_eq!(output, r#"[0,"",{"Circle":0.0},[false]]"#);
+}
+
+fn from_json<'de, T, G>(input: &'de str, garbler: &mut G) -> T
+where
+    T: Deserialize<'de>,
+    G: garble::Garbler,
+{
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+    T::deserialize(GarblingDeserializer::new(&mut deserializer, garbler)).unwrap()
+}
+
+#[test]
+fn test_deserializer_passthrough() {
+    // GIVEN a serialized value
+    let value = item();
+    let input = serde_json::to_string(&value).unwrap();
+    // WHEN we deserialize it with a garbler that does not change anything
+    let mut garbler = CountingGarbler::new();
+    let output: Item = from_json(&input, &mut garbler);
+    // THEN the value should be the same as the original
+    assert_eq!(output, value);
+    // AND every primitive leaf should be garbled, but not map keys
+    //   the IP addresses are deserialized from strings in JSON
+    assert_eq!(garbler.count(), 11 + 3);
+}
+
+#[test]
+fn test_deserializer_zero() {
+    // GIVEN a serialized value
+    let input = r#"[1,"hello",{"Circle":1.5},[true],{"Rect":{"width":3,"height":4}}]"#;
+    // WHEN we deserialize it with a ZeroGarbler
+    let output: (u32, String, Option<Shape>, Vec<bool>, Shape) = from_json(input, &mut ZeroGarbler);
+    // THEN the leaves should be zeroed, while the structure is kept
+    assert_eq!(
+        output,
+        (
+            0,
+            String::new(),
+            Some(Shape::Circle(0.0)),
+            vec![false],
+            Shape::Rect {
+                width: 0,
+                height: 0
+            }
+        )
+    );
+}
+
+#[test]
+fn test_deserializer_internally_tagged() {
+    // GIVEN serialized internally tagged enums, with the tag first or last
+    let cases = [
+        (r#"{"type":"Click","x":1}"#, Event::Click { x: 0 }),
+        (r#"{"code":3,"type":"Key"}"#, Event::Key { code: 0 }),
+    ];
+    for (input, expected) in cases {
+        // WHEN we deserialize them with a ZeroGarbler
+        let output: Event = from_json(input, &mut ZeroGarbler);
+        // THEN the content should be zeroed, while the tag is kept
+        assert_eq!(output, expected);
+    }
+    // AND only the content should be garbled
+    let mut garbler = CountingGarbler::new();
+    let _: Event = from_json(r#"{"type":"Click","x":1}"#, &mut garbler);
+    assert_eq!(garbler.count(), 1);
+}
+
+#[test]
+fn test_deserializer_borrowed() {
+    // GIVEN a serialized value with a borrowed string
+    let input = r#"["hello"]"#;
+    // WHEN we deserialize it with a garbler that does not change anything
+    let output: (&str,) = from_json(input, &mut CountingGarbler::new());
+    // THEN the string should still borrow from the input
+    assert_eq!(output, ("hello",));
+}
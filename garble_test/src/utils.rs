@@ -38,3 +38,37 @@ impl Garbler for ZeroGarbler {
         String::new()
     }
 }
+
+/// Garbler that only implements the required functions, and counts how many
+/// primitive values it was asked to garble
+#[derive(Default)]
+pub(crate) struct PrimitiveCountingGarbler {
+    pub(crate) count: usize,
+}
+
+macro_rules! impl_count_func {
+    ($($t:ty),*) => {
+        $(
+            paste! {
+                fn [<garble_ $t:lower>](&mut self, value: $t) -> $t {
+                    self.count += 1;
+                    value
+                }
+            }
+        )*
+    };
+}
+
+impl Garbler for PrimitiveCountingGarbler {
+    impl_count_func!(
+        bool, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, char
+    );
+
+    fn garble_str<T>(&mut self, value: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        self.count += 1;
+        value.as_ref().to_string()
+    }
+}
@@ -45,3 +45,30 @@ fn test_enum_v2() {
     let output = input.garble(&mut ZeroGarbler);
     assert_eq!(output, expected);
 }
+
+#[derive(Garble, Clone, Debug, PartialEq)]
+struct Pointers {
+    a: Box<u32>,
+    b: std::rc::Rc<u32>,
+    c: std::sync::Arc<String>,
+    d: std::borrow::Cow<'static, str>,
+}
+
+#[test]
+fn test_pointers() {
+    let input = Pointers {
+        a: Box::new(1),
+        b: std::rc::Rc::new(2),
+        c: std::sync::Arc::new("hello".to_string()),
+        d: std::borrow::Cow::Borrowed("world"),
+    };
+    let expected = Pointers {
+        a: Box::new(0),
+        b: std::rc::Rc::new(0),
+        c: std::sync::Arc::new(String::new()),
+        d: std::borrow::Cow::Owned(String::new()),
+    };
+
+    let output = input.garble(&mut ZeroGarbler);
+    assert_eq!(output, expected);
+}